@@ -0,0 +1,14 @@
+ALTER TABLE posts ADD file_hashes text[] NOT NULL DEFAULT '{}';
+
+CREATE TABLE post_file_history (
+	post_id int NOT NULL REFERENCES posts ON DELETE CASCADE,
+	file_name text NOT NULL,
+	file_hash text,
+	filesize bigint,
+	time timestamp NOT NULL
+);
+
+INSERT INTO post_file_history (post_id, file_name, filesize, time)
+SELECT p.id, regexp_replace(f.file_name, '^.*/', ''), f.filesize, p.time
+FROM posts p, unnest(p.local_files, p.filesizes) AS f(file_name, filesize)
+WHERE f.file_name IS NOT NULL;
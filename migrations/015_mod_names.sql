@@ -0,0 +1,7 @@
+CREATE TABLE post_mod_names (
+	post_id int NOT NULL REFERENCES posts ON DELETE CASCADE,
+	name text NOT NULL
+);
+
+CREATE INDEX post_mod_names_name ON post_mod_names (lower(name));
+CREATE INDEX post_file_history_hash ON post_file_history (file_hash);
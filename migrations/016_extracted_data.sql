@@ -0,0 +1,53 @@
+-- post_id is -1 for base game entries, so these can't reference posts
+
+CREATE TABLE pvs (
+	post_id int NOT NULL,
+	pv_id int NOT NULL,
+	song_name text NOT NULL,
+	song_name_en text NOT NULL,
+	song_info jsonb,
+	song_info_en jsonb,
+	levels jsonb NOT NULL,
+	PRIMARY KEY (post_id, pv_id)
+);
+
+CREATE INDEX pvs_pv_id ON pvs (pv_id);
+
+CREATE TABLE modules (
+	post_id int NOT NULL,
+	module_id int NOT NULL,
+	chara text NOT NULL,
+	cos_id int NOT NULL,
+	module jsonb NOT NULL,
+	PRIMARY KEY (post_id, module_id)
+);
+
+CREATE INDEX modules_module_id ON modules (module_id);
+CREATE INDEX modules_cos ON modules (chara, cos_id);
+
+CREATE TABLE cstm_items (
+	post_id int NOT NULL,
+	customize_item_id int NOT NULL,
+	customize_item jsonb NOT NULL,
+	PRIMARY KEY (post_id, customize_item_id)
+);
+
+CREATE INDEX cstm_items_customize_item_id ON cstm_items (customize_item_id);
+
+CREATE TABLE nc_songs (
+	post_id int NOT NULL,
+	pv_id int NOT NULL,
+	difficulties jsonb NOT NULL,
+	PRIMARY KEY (post_id, pv_id)
+);
+
+-- db_type is the name of the meilisearch index the entry belongs in
+CREATE TABLE db_entries (
+	db_type text NOT NULL,
+	post_id int NOT NULL,
+	id bigint NOT NULL,
+	name text NOT NULL,
+	PRIMARY KEY (db_type, post_id, id)
+);
+
+CREATE INDEX db_entries_post_id ON db_entries (post_id);
@@ -0,0 +1,10 @@
+CREATE TABLE base_game_imports (
+	id serial PRIMARY KEY,
+	version text NOT NULL,
+	replaced bool NOT NULL,
+	pvs bigint NOT NULL,
+	modules bigint NOT NULL,
+	cstm_items bigint NOT NULL,
+	db_entries bigint NOT NULL,
+	time timestamp NOT NULL
+);
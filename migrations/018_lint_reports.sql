@@ -0,0 +1,6 @@
+CREATE TABLE post_lint_reports (
+	post_id int PRIMARY KEY REFERENCES posts ON DELETE CASCADE,
+	lints jsonb NOT NULL,
+	admin_override bool NOT NULL DEFAULT false,
+	time timestamp NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE post_lint_reports ADD COLUMN archive_lints jsonb NOT NULL DEFAULT '[]';
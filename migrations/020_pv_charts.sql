@@ -0,0 +1 @@
+ALTER TABLE pvs ADD COLUMN charts jsonb NOT NULL DEFAULT '[null, null, null, null, null]';
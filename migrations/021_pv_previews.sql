@@ -0,0 +1 @@
+ALTER TABLE pvs ADD COLUMN preview bool NOT NULL DEFAULT false;
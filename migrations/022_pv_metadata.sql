@@ -0,0 +1 @@
+ALTER TABLE pvs ADD COLUMN metadata jsonb NOT NULL DEFAULT '{}';
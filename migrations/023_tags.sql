@@ -0,0 +1,12 @@
+CREATE TABLE tags (
+	id serial PRIMARY KEY,
+	name text NOT NULL UNIQUE,
+	category int NOT NULL DEFAULT 5,
+	curated bool NOT NULL DEFAULT false
+);
+
+CREATE TABLE post_tags (
+	post_id int NOT NULL REFERENCES posts ON DELETE CASCADE,
+	tag_id int NOT NULL REFERENCES tags ON DELETE CASCADE,
+	PRIMARY KEY (post_id, tag_id)
+);
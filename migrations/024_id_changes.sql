@@ -0,0 +1,15 @@
+-- Append only log of ids added, removed or renamed by uploads and of reservation changes
+-- post_id is kept after the post is deleted so clients can still see where removals came from
+
+CREATE TABLE id_changes (
+	seq bigserial PRIMARY KEY,
+	time timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	reservation_type int NOT NULL,
+	id int NOT NULL,
+	change int NOT NULL,
+	post_id int,
+	user_id bigint,
+	name text
+);
+
+CREATE INDEX id_changes_time ON id_changes (time);
@@ -0,0 +1,11 @@
+-- client is a salted hash of the downloaders ip and user agent, or of their user id when logged in
+CREATE TABLE download_events (
+	post_id int NOT NULL REFERENCES posts ON DELETE CASCADE,
+	variant int NOT NULL,
+	day date NOT NULL DEFAULT CURRENT_DATE,
+	time timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	client text NOT NULL
+);
+
+CREATE INDEX download_events_post_day ON download_events (post_id, day);
+CREATE INDEX download_events_client ON download_events (client, post_id, time);
@@ -0,0 +1,3 @@
+-- Likes made before this migration have no time and are left out of activity graphs
+ALTER TABLE liked_posts ADD time timestamp;
+ALTER TABLE liked_posts ALTER time SET DEFAULT CURRENT_TIMESTAMP;
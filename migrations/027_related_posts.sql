@@ -0,0 +1,7 @@
+-- Rebuilt from scratch by update_related_posts, only holds the highest scoring posts for each post
+CREATE TABLE related_posts (
+	post_id int NOT NULL REFERENCES posts ON DELETE CASCADE,
+	related_id int NOT NULL REFERENCES posts ON DELETE CASCADE,
+	score real NOT NULL,
+	PRIMARY KEY (post_id, related_id)
+);
@@ -0,0 +1,16 @@
+-- path is kept so earlier imports can be re-applied when a later one is rolled back
+ALTER TABLE base_game_imports ADD COLUMN path text;
+ALTER TABLE base_game_imports ADD COLUMN rolled_back bool NOT NULL DEFAULT false;
+
+-- The import a base game entry last came from, always null for entries of posts
+ALTER TABLE pvs ADD COLUMN import_id int REFERENCES base_game_imports;
+ALTER TABLE modules ADD COLUMN import_id int REFERENCES base_game_imports;
+ALTER TABLE cstm_items ADD COLUMN import_id int REFERENCES base_game_imports;
+ALTER TABLE nc_songs ADD COLUMN import_id int REFERENCES base_game_imports;
+ALTER TABLE db_entries ADD COLUMN import_id int REFERENCES base_game_imports;
+
+UPDATE pvs SET import_id = (SELECT MAX(id) FROM base_game_imports) WHERE post_id = -1;
+UPDATE modules SET import_id = (SELECT MAX(id) FROM base_game_imports) WHERE post_id = -1;
+UPDATE cstm_items SET import_id = (SELECT MAX(id) FROM base_game_imports) WHERE post_id = -1;
+UPDATE nc_songs SET import_id = (SELECT MAX(id) FROM base_game_imports) WHERE post_id = -1;
+UPDATE db_entries SET import_id = (SELECT MAX(id) FROM base_game_imports) WHERE post_id = -1;
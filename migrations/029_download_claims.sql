@@ -0,0 +1,15 @@
+-- The last counted download of each file by each client, the primary key makes the dedup race free
+CREATE TABLE download_claims (
+	client text NOT NULL,
+	post_id int NOT NULL REFERENCES posts ON DELETE CASCADE,
+	variant int NOT NULL,
+	time timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY (client, post_id, variant)
+);
+
+CREATE INDEX download_claims_time ON download_claims (time);
+
+INSERT INTO download_claims (client, post_id, variant, time)
+SELECT client, post_id, variant, MAX(time)
+FROM download_events
+GROUP BY client, post_id, variant;
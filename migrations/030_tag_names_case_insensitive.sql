@@ -0,0 +1,18 @@
+-- Tags differing only in case are merged into the curated or oldest one
+CREATE TEMPORARY TABLE tag_merges AS
+SELECT id, FIRST_VALUE(id) OVER (PARTITION BY lower(name) ORDER BY curated DESC, id) AS keep_id
+FROM tags;
+
+INSERT INTO post_tags (post_id, tag_id)
+SELECT pt.post_id, m.keep_id
+FROM post_tags pt
+INNER JOIN tag_merges m ON pt.tag_id = m.id
+WHERE m.id != m.keep_id
+ON CONFLICT DO NOTHING;
+
+DELETE FROM tags WHERE id IN (SELECT id FROM tag_merges WHERE id != keep_id);
+
+DROP TABLE tag_merges;
+
+ALTER TABLE tags DROP CONSTRAINT tags_name_key;
+CREATE UNIQUE INDEX tags_name_lower ON tags (lower(name));
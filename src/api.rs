@@ -1,21 +1,58 @@
 use crate::AppState;
 use axum::{Router, routing::*};
+use catalogue::*;
+use changes::*;
+use compatibility::*;
+use dashboard::*;
+use downloads::*;
 use ids::*;
+use lint::*;
 use posts::*;
+use songs::*;
+use suggest::*;
+use tags::*;
 use utoipa::OpenApi;
 
+pub mod catalogue;
+pub mod changes;
+pub mod compatibility;
+pub mod dashboard;
+pub mod downloads;
 pub mod ids;
+pub mod lint;
 pub mod posts;
+pub mod songs;
+pub mod suggest;
+pub mod tags;
 
 #[derive(OpenApi)]
 #[openapi(paths(
 	search_posts,
 	count_posts,
+	search_posts_faceted,
+	suggest,
 	get_post,
 	post_detail,
+	lint_report,
+	override_lint_report,
+	nc_compatibility_report,
+	post_downloads,
+	author_downloads,
+	dashboard,
+	set_post_tags,
+	list_tags,
+	create_tag,
+	delete_tag,
+	check_updates,
+	identify_archive,
 	search_pvs,
+	pv_detail,
+	song_versions,
+	search_songs,
 	search_pvs_and_reservations,
 	search_modules,
+	module_detail,
+	cos_detail,
 	search_cstm_items,
 	search_nc_songs,
 	all_pvs,
@@ -26,7 +63,14 @@ pub mod posts;
 	all_aet_sets,
 	all_aet_scenes,
 	all_objsets,
-	all_textures
+	all_textures,
+	catalogue,
+	id_changes,
+	start_reextract,
+	reextract_progress,
+	start_base_game_import,
+	base_game_imports,
+	rollback_base_game
 ))]
 struct ApiDoc;
 
@@ -35,12 +79,31 @@ pub fn route(state: AppState) -> Router {
 		.merge(utoipa_swagger_ui::SwaggerUi::new("/api/v1").url("/api/v1.json", ApiDoc::openapi()))
 		.route("/api/v1/posts", get(search_posts).post(create_post))
 		.route("/api/v1/posts/count", get(count_posts))
+		.route("/api/v1/posts/facets", get(search_posts_faceted))
+		.route("/api/v1/suggest", get(suggest))
 		.route(
 			"/api/v1/posts/{id}",
 			get(get_post).delete(delete_post).patch(edit_post),
 		)
 		.route("/api/v1/posts/{id}/detail", get(post_detail))
+		.route("/api/v1/posts/{id}/lint", get(lint_report))
+		.route(
+			"/api/v1/posts/{id}/nc_compatibility",
+			get(nc_compatibility_report),
+		)
+		.route("/api/v1/posts/{id}/tags", put(set_post_tags))
+		.route("/api/v1/posts/{id}/downloads", get(post_downloads))
+		.route("/api/v1/users/{id}/downloads", get(author_downloads))
+		.route("/api/v1/users/{id}/dashboard", get(dashboard))
+		.route("/api/v1/tags", get(list_tags).post(create_tag))
+		.route("/api/v1/tags/{id}", delete(delete_tag))
+		.route(
+			"/api/v1/posts/{id}/lint/override",
+			post(override_lint_report),
+		)
 		.route("/api/v1/posts/posts", get(get_multiple_posts))
+		.route("/api/v1/posts/updates", post(check_updates))
+		.route("/api/v1/posts/identify", post(identify_archive))
 		.route("/api/v1/posts/upload_image", get(upload_image))
 		.route("/api/v1/posts/start_upload", post(create_pending_upload))
 		.route(
@@ -76,11 +139,19 @@ pub fn route(state: AppState) -> Router {
 		)
 		.route("/api/v1/users/settings", post(user_settings))
 		.route("/api/v1/ids/pvs", get(search_pvs))
+		.route("/api/v1/ids/pvs/{post}/{pv_id}", get(pv_detail))
+		.route(
+			"/api/v1/ids/pvs/{post}/{pv_id}/versions",
+			get(song_versions),
+		)
+		.route("/api/v1/ids/songs", get(search_songs))
 		.route(
 			"/api/v1/ids/pvs_and_reservations",
 			get(search_pvs_and_reservations),
 		)
 		.route("/api/v1/ids/modules", get(search_modules))
+		.route("/api/v1/ids/modules/{post}/{module_id}", get(module_detail))
+		.route("/api/v1/ids/cos/{chara}/{cos_id}", get(cos_detail))
 		.route("/api/v1/ids/cstm_items", get(search_cstm_items))
 		.route("/api/v1/ids/nc_songs", get(search_nc_songs))
 		.route("/api/v1/ids/all_pvs", get(all_pvs))
@@ -92,6 +163,20 @@ pub fn route(state: AppState) -> Router {
 		.route("/api/v1/ids/all_aet_scenes", get(all_aet_scenes))
 		.route("/api/v1/ids/all_objsets", get(all_objsets))
 		.route("/api/v1/ids/all_textures", get(all_textures))
+		.route("/api/v1/ids/catalogue/{kind}", get(catalogue))
+		.route("/api/v1/ids/changes", get(id_changes))
+		.route(
+			"/api/v1/ids/reextract",
+			get(reextract_progress).post(start_reextract),
+		)
+		.route(
+			"/api/v1/ids/base_game",
+			get(base_game_imports).post(start_base_game_import),
+		)
+		.route(
+			"/api/v1/ids/base_game/{id}/rollback",
+			post(rollback_base_game),
+		)
 		.route("/api/v1/reserve/check", get(web_check_reserve_range))
 		.route("/api/v1/reserve/find", get(web_find_reserve_range))
 		.route(
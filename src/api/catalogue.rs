@@ -0,0 +1,362 @@
+use crate::AppState;
+use crate::api::ids::*;
+use axum::{
+	body::Body,
+	extract::*,
+	http::{HeaderMap, StatusCode, header},
+	response::*,
+};
+use base64::prelude::*;
+use itertools::*;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::Digest;
+use utoipa::{IntoParams, ToSchema};
+
+const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogueKind {
+	Pvs,
+	Modules,
+	CstmItems,
+	SpriteSets,
+	Sprites,
+	AetSets,
+	AetScenes,
+	Objsets,
+	Textures,
+}
+
+impl CatalogueKind {
+	fn index(&self) -> &'static str {
+		match self {
+			Self::Pvs => "pvs",
+			Self::Modules => "modules",
+			Self::CstmItems => "cstm_items",
+			Self::SpriteSets => "sprite_sets",
+			Self::Sprites => "sprites",
+			Self::AetSets => "aet_sets",
+			Self::AetScenes => "aet_scenes",
+			Self::Objsets => "objsets",
+			Self::Textures => "textures",
+		}
+	}
+
+	/// Attributes entries are ordered by before the uid, the cursor is the last value of each
+	fn order(&self) -> (&'static str, &'static str) {
+		match self {
+			Self::Pvs => ("post", "pv_id"),
+			Self::Modules => ("post_id", "module_id"),
+			Self::CstmItems => ("post_id", "customize_item_id"),
+			_ => ("post_id", "id"),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogueFormat {
+	#[default]
+	Json,
+	Ndjson,
+}
+
+impl CatalogueFormat {
+	fn name(&self) -> &'static str {
+		match self {
+			Self::Json => "json",
+			Self::Ndjson => "ndjson",
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, IntoParams)]
+pub struct CatalogueParams {
+	/// The `next` cursor of the previous page, leave empty to start from the beginning
+	pub after: Option<String>,
+	/// Entries per page, at most 1000
+	pub limit: Option<usize>,
+	/// `json` returns a single page, `ndjson` streams every entry after the cursor one per line
+	pub format: Option<CatalogueFormat>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum CatalogueEntry {
+	Pv(Pv),
+	Module(Module),
+	CstmItem(CstmItem),
+	DbEntry(MeilisearchDbEntry),
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CataloguePage {
+	pub entries: Vec<CatalogueEntry>,
+	/// Cursor for the next page, none once the end of the catalogue is reached
+	pub next: Option<String>,
+}
+
+type Cursor = (i64, i64, u64);
+
+fn encode_cursor((post, id, uid): Cursor) -> String {
+	BASE64_URL_SAFE_NO_PAD.encode(format!("{post}:{id}:{uid}"))
+}
+
+fn decode_cursor(cursor: &str) -> Option<Cursor> {
+	let cursor = String::from_utf8(BASE64_URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+	let (post, id, uid) = cursor.split(':').collect_tuple()?;
+	Some((post.parse().ok()?, id.parse().ok()?, uid.parse().ok()?))
+}
+
+fn post(post_id: i32) -> Option<i32> {
+	if post_id == -1 { None } else { Some(post_id) }
+}
+
+async fn fetch_page(
+	kind: CatalogueKind,
+	after: Option<Cursor>,
+	limit: usize,
+	state: &AppState,
+) -> Result<CataloguePage, (StatusCode, String)> {
+	let index = state.meilisearch.index(kind.index());
+	let mut search = meilisearch_sdk::search::SearchQuery::new(&index);
+
+	let (post_attribute, id_attribute) = kind.order();
+	let filter = after.map(|(post, id, uid)| {
+		format!(
+			"{post_attribute} > {post} OR ({post_attribute} = {post} AND ({id_attribute} > {id} OR ({id_attribute} = {id} AND uid > {uid})))"
+		)
+	});
+	if let Some(filter) = &filter {
+		search.filter = Some(meilisearch_sdk::search::Filter::new(sqlx::Either::Left(
+			filter.as_str(),
+		)));
+	}
+
+	let post_sort = format!("{post_attribute}:asc");
+	let id_sort = format!("{id_attribute}:asc");
+	let sort = [post_sort.as_str(), id_sort.as_str(), "uid:asc"];
+	search.sort = Some(&sort);
+	search.limit = Some(limit);
+
+	let map_err = |e: meilisearch_sdk::errors::Error| (StatusCode::BAD_REQUEST, e.to_string());
+
+	let mut last = None;
+	let entries = match kind {
+		CatalogueKind::Pvs => {
+			let results = search.execute::<MeilisearchPv>().await.map_err(map_err)?;
+			results
+				.hits
+				.into_iter()
+				.map(|hit| {
+					let pv = hit.result;
+					last = Some((pv.post as i64, pv.pv_id as i64, pv.uid));
+					CatalogueEntry::Pv(Pv {
+						uid: BASE64_STANDARD.encode(pv.uid.to_ne_bytes()),
+						id: pv.pv_id,
+						preview_url: pv.preview_url(),
+						name: pv.song_name,
+						name_en: pv.song_name_en,
+						song_info: pv.song_info,
+						song_info_en: pv.song_info_en,
+						levels: pv.levels,
+						charts: pv.charts,
+						metadata: pv.metadata,
+						post: post(pv.post),
+					})
+				})
+				.collect::<Vec<_>>()
+		}
+		CatalogueKind::Modules => {
+			let results = search
+				.execute::<MeilisearchModule>()
+				.await
+				.map_err(map_err)?;
+			results
+				.hits
+				.into_iter()
+				.map(|hit| {
+					let module = hit.result;
+					last = Some((module.post_id as i64, module.module_id as i64, module.uid));
+					CatalogueEntry::Module(Module {
+						uid: BASE64_STANDARD.encode(module.uid.to_ne_bytes()),
+						post: post(module.post_id),
+						id: module.module_id,
+						module: module.module,
+					})
+				})
+				.collect::<Vec<_>>()
+		}
+		CatalogueKind::CstmItems => {
+			let results = search
+				.execute::<MeilisearchCstmItem>()
+				.await
+				.map_err(map_err)?;
+			results
+				.hits
+				.into_iter()
+				.map(|hit| {
+					let mut cstm_item = hit.result;
+					last = Some((
+						cstm_item.post_id as i64,
+						cstm_item.customize_item_id as i64,
+						cstm_item.uid,
+					));
+					if cstm_item.customize_item.bind_module == Some(-1) {
+						cstm_item.customize_item.bind_module = None;
+					}
+					CatalogueEntry::CstmItem(CstmItem {
+						uid: BASE64_STANDARD.encode(cstm_item.uid.to_ne_bytes()),
+						post: post(cstm_item.post_id),
+						id: cstm_item.customize_item_id,
+						cstm_item: cstm_item.customize_item,
+					})
+				})
+				.collect::<Vec<_>>()
+		}
+		_ => {
+			let results = search
+				.execute::<MeilisearchDbEntry>()
+				.await
+				.map_err(map_err)?;
+			results
+				.hits
+				.into_iter()
+				.map(|hit| {
+					let entry = hit.result;
+					last = Some((entry.post_id as i64, entry.id as i64, entry.uid));
+					CatalogueEntry::DbEntry(entry)
+				})
+				.collect::<Vec<_>>()
+		}
+	};
+
+	let next = if entries.len() == limit {
+		last.map(encode_cursor)
+	} else {
+		None
+	};
+
+	Ok(CataloguePage { entries, next })
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/ids/catalogue/{kind}",
+	params(
+		("kind" = CatalogueKind, Path),
+		CatalogueParams
+	),
+	responses(
+		(status = 200, body = CataloguePage, content_type = "application/json"),
+		(status = 200, body = CatalogueEntry, content_type = "application/x-ndjson", description = "One entry per line when format=ndjson"),
+		(status = 304),
+		(status = 400, body = String)
+	)
+)]
+pub async fn catalogue(
+	Path(kind): Path<CatalogueKind>,
+	Query(params): Query<CatalogueParams>,
+	headers: HeaderMap,
+	State(state): State<AppState>,
+) -> Result<Response, (StatusCode, String)> {
+	let after = match &params.after {
+		Some(cursor) => Some(
+			decode_cursor(cursor)
+				.ok_or((StatusCode::BAD_REQUEST, String::from("Invalid cursor")))?,
+		),
+		None => None,
+	};
+	let limit = params
+		.limit
+		.unwrap_or(MAX_PAGE_SIZE)
+		.clamp(1, MAX_PAGE_SIZE);
+	let format = params.format.unwrap_or_default();
+
+	// Any write to the index bumps updated_at, so unchanged catalogues can be answered with a 304
+	let updated_at = state
+		.meilisearch
+		.get_index(kind.index())
+		.await
+		.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+		.updated_at
+		.map_or(0, |time| time.unix_timestamp_nanos());
+	let mut hasher = sha2::Sha256::new();
+	hasher.update(kind.index().as_bytes());
+	hasher.update(updated_at.to_le_bytes());
+	hasher.update(params.after.as_deref().unwrap_or_default().as_bytes());
+	hasher.update(limit.to_le_bytes());
+	hasher.update(format.name().as_bytes());
+	let etag = format!("\"{:x}\"", hasher.finalize());
+
+	if headers
+		.get(header::IF_NONE_MATCH)
+		.and_then(|value| value.to_str().ok())
+		.is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag))
+	{
+		return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+	}
+
+	if format == CatalogueFormat::Json {
+		let page = fetch_page(kind, after, limit, &state).await?;
+		return Ok(([(header::ETAG, etag)], Json(page)).into_response());
+	}
+
+	let stream = futures::stream::unfold(Some(after), move |cursor| {
+		let state = state.clone();
+		async move {
+			let after = cursor?;
+			let page = match fetch_page(kind, after, limit, &state).await {
+				Ok(page) => page,
+				Err((_, e)) => return Some((Err(std::io::Error::other(e)), None)),
+			};
+			if page.entries.is_empty() {
+				return None;
+			}
+
+			let mut lines = String::new();
+			for entry in &page.entries {
+				if let Ok(line) = serde_json::to_string(entry) {
+					lines.push_str(&line);
+					lines.push('\n');
+				}
+			}
+
+			let next = page.next.as_deref().and_then(decode_cursor).map(Some);
+			Some((Ok(lines), next))
+		}
+	});
+
+	Ok((
+		[
+			(header::CONTENT_TYPE, String::from("application/x-ndjson")),
+			(header::ETAG, etag),
+		],
+		Body::from_stream(stream),
+	)
+		.into_response())
+}
+
+/// Reads every document of an index a page at a time, for pages that have to show the whole index at once
+pub async fn all_documents<T: DeserializeOwned + 'static>(
+	index: &str,
+	filter: Option<&str>,
+	state: &AppState,
+) -> Result<Vec<T>, meilisearch_sdk::errors::Error> {
+	let index = state.meilisearch.index(index);
+	let mut documents = Vec::new();
+	loop {
+		let mut query = meilisearch_sdk::documents::DocumentsQuery::new(&index);
+		query.with_offset(documents.len()).with_limit(MAX_PAGE_SIZE);
+		if let Some(filter) = filter {
+			query.with_filter(filter);
+		}
+		let mut page = query.execute::<T>().await?.results;
+		let done = page.len() < MAX_PAGE_SIZE;
+		documents.append(&mut page);
+		if done {
+			return Ok(documents);
+		}
+	}
+}
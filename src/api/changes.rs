@@ -0,0 +1,252 @@
+use crate::AppState;
+use crate::api::ids::*;
+use axum::{extract::*, http::StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::*;
+use utoipa::{IntoParams, ToSchema};
+
+const MAX_CHANGES: i64 = 1000;
+/// seq is taken when a change is inserted rather than when it commits, so a change can become
+/// visible after one with a higher seq. Changes younger than this are held back until every
+/// transaction that could still commit a lower seq has finished, so clients never skip one
+const SETTLE_SECONDS: f64 = 5.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, ToSchema)]
+#[repr(i32)]
+pub enum IdChangeKind {
+	/// Added by an upload or edit of a post
+	Added = 0,
+	/// Removed by an edit or deletion of a post
+	Removed = 1,
+	/// Still exists in the post but under a different name
+	Renamed = 2,
+	Reserved = 3,
+	Unreserved = 4,
+	/// A reserved id was given a label, the label is the name of the change
+	Labelled = 5,
+}
+
+impl From<i32> for IdChangeKind {
+	fn from(value: i32) -> Self {
+		match value {
+			1 => Self::Removed,
+			2 => Self::Renamed,
+			3 => Self::Reserved,
+			4 => Self::Unreserved,
+			5 => Self::Labelled,
+			_ => Self::Added,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct IdChange {
+	pub seq: i64,
+	#[serde(with = "time::serde::rfc3339")]
+	pub time: time::OffsetDateTime,
+	pub reservation_type: ReservationType,
+	pub id: i32,
+	pub change: IdChangeKind,
+	/// The post the id was uploaded in, none for reservation changes
+	pub post: Option<i32>,
+	/// The user who reserved the id, none for upload changes
+	pub user: Option<i64>,
+	pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct IdChanges {
+	pub changes: Vec<IdChange>,
+	/// Pass as since_seq to get the next batch of changes
+	pub next_seq: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, IntoParams)]
+pub struct IdChangesParams {
+	/// Only return changes with a sequence number above this
+	pub since_seq: Option<i64>,
+	/// Only return changes made at or after this unix timestamp
+	pub since: Option<i64>,
+	/// At most 1000
+	pub limit: Option<i64>,
+}
+
+/// The pv, module and cstm_item ids a post currently has extracted, along with their names
+pub type ExtractedIds = BTreeMap<(ReservationType, i32), String>;
+
+pub async fn extracted_ids(post_id: i32, state: &AppState) -> ExtractedIds {
+	let mut ids = BTreeMap::new();
+
+	let pvs = sqlx::query!(
+		"SELECT pv_id, song_name, song_name_en FROM pvs WHERE post_id = $1",
+		post_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default();
+	for pv in pvs {
+		let name = if pv.song_name_en.is_empty() {
+			pv.song_name
+		} else {
+			pv.song_name_en
+		};
+		ids.insert((ReservationType::Song, pv.pv_id), name);
+	}
+
+	let modules = sqlx::query!(
+		r#"
+		SELECT module_id, module->>'name_en' AS name_en, module->>'name' AS name
+		FROM modules WHERE post_id = $1
+		"#,
+		post_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default();
+	for module in modules {
+		ids.insert(
+			(ReservationType::Module, module.module_id),
+			module.name_en.or(module.name).unwrap_or_default(),
+		);
+	}
+
+	let cstm_items = sqlx::query!(
+		r#"
+		SELECT customize_item_id, customize_item->>'name_en' AS name_en, customize_item->>'name' AS name
+		FROM cstm_items WHERE post_id = $1
+		"#,
+		post_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default();
+	for cstm_item in cstm_items {
+		ids.insert(
+			(ReservationType::CstmItem, cstm_item.customize_item_id),
+			cstm_item.name_en.or(cstm_item.name).unwrap_or_default(),
+		);
+	}
+
+	ids
+}
+
+/// Appends a change for every given id, empty names are stored as none
+pub async fn log_id_changes(
+	reservation_type: ReservationType,
+	change: IdChangeKind,
+	ids: &[(i32, String)],
+	post: Option<i32>,
+	user: Option<i64>,
+	state: &AppState,
+) {
+	if ids.is_empty() {
+		return;
+	}
+
+	let (ids, names): (Vec<_>, Vec<_>) = ids.iter().cloned().unzip();
+	_ = sqlx::query!(
+		r#"
+		INSERT INTO id_changes (reservation_type, id, change, post_id, user_id, name)
+		SELECT $1, t.id, $2, $3, $4, NULLIF(t.name, '')
+		FROM UNNEST($5::int[], $6::text[]) AS t(id, name)
+		"#,
+		reservation_type as i32,
+		change as i32,
+		post,
+		user,
+		&ids,
+		&names
+	)
+	.execute(&state.db)
+	.await;
+}
+
+/// Compares a snapshot taken with `extracted_ids` against what the post has extracted now and logs the difference
+pub async fn log_extraction_changes(post_id: i32, before: ExtractedIds, state: &AppState) {
+	let after = extracted_ids(post_id, state).await;
+
+	let mut changes: BTreeMap<(ReservationType, IdChangeKind), Vec<(i32, String)>> =
+		BTreeMap::new();
+	for ((reservation_type, id), name) in &after {
+		let change = match before.get(&(*reservation_type, *id)) {
+			None => IdChangeKind::Added,
+			Some(old_name) if old_name != name => IdChangeKind::Renamed,
+			Some(_) => continue,
+		};
+		changes
+			.entry((*reservation_type, change))
+			.or_default()
+			.push((*id, name.clone()));
+	}
+	for ((reservation_type, id), name) in before {
+		if !after.contains_key(&(reservation_type, id)) {
+			changes
+				.entry((reservation_type, IdChangeKind::Removed))
+				.or_default()
+				.push((id, name));
+		}
+	}
+
+	for ((reservation_type, change), ids) in changes {
+		log_id_changes(reservation_type, change, &ids, Some(post_id), None, state).await;
+	}
+}
+
+/// Ids added, removed or renamed by uploads and reservation changes, oldest first. Changes only show up a few seconds after they are made
+#[utoipa::path(
+	get,
+	path = "/api/v1/ids/changes",
+	params(IdChangesParams),
+	responses(
+		(status = 200, body = IdChanges, content_type = "application/json"),
+		(status = 400),
+		(status = 500)
+	)
+)]
+pub async fn id_changes(
+	Query(params): Query<IdChangesParams>,
+	State(state): State<AppState>,
+) -> Result<Json<IdChanges>, StatusCode> {
+	let since_seq = params.since_seq.unwrap_or(0);
+	let since = match params.since {
+		Some(since) => {
+			time::OffsetDateTime::from_unix_timestamp(since).map_err(|_| StatusCode::BAD_REQUEST)?
+		}
+		None => time::OffsetDateTime::UNIX_EPOCH,
+	};
+	let since = time::PrimitiveDateTime::new(since.date(), since.time());
+	let limit = params.limit.unwrap_or(MAX_CHANGES).clamp(1, MAX_CHANGES);
+
+	let changes = sqlx::query!(
+		r#"
+		SELECT * FROM id_changes
+		WHERE seq > $1 AND time >= $2
+		AND time < CURRENT_TIMESTAMP - make_interval(secs => $4)
+		ORDER BY seq
+		LIMIT $3
+		"#,
+		since_seq,
+		since,
+		limit,
+		SETTLE_SECONDS
+	)
+	.fetch_all(&state.db)
+	.await
+	.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+	.into_iter()
+	.map(|change| IdChange {
+		seq: change.seq,
+		time: change.time.assume_offset(time::UtcOffset::UTC),
+		reservation_type: change.reservation_type.into(),
+		id: change.id,
+		change: change.change.into(),
+		post: change.post_id,
+		user: change.user_id,
+		name: change.name,
+	})
+	.collect::<Vec<_>>();
+
+	let next_seq = changes.last().map_or(since_seq, |change| change.seq);
+
+	Ok(Json(IdChanges { changes, next_seq }))
+}
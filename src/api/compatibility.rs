@@ -0,0 +1,222 @@
+use crate::AppState;
+use crate::api::ids::*;
+use crate::models::*;
+use axum::{extract::*, http::StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::*;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum ChartSource {
+	/// Defined by the posts own pv_db or nc_db
+	Post,
+	/// Inherited from the base games pv_db
+	BaseGame,
+	/// Needs the pv_db of another post to be installed
+	OtherPost(i32),
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct CompatibilityChart {
+	pub level: Option<pv_db::Level>,
+	pub source: ChartSource,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct CompatibilityDifficulty {
+	pub arcade: Option<CompatibilityChart>,
+	pub console: Option<CompatibilityChart>,
+	pub mixed: Option<CompatibilityChart>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct CompatibilitySong {
+	pub pv_id: i32,
+	pub name: Option<String>,
+	/// Where the pv_db entry of the song comes from, none if it could not be found
+	pub pv_source: Option<ChartSource>,
+	pub difficulties: [CompatibilityDifficulty; 5],
+	pub has_nc_chart: bool,
+	pub works_without_nc: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct NcCompatibility {
+	pub post_id: i32,
+	pub songs: Vec<CompatibilitySong>,
+	pub works_without_nc: bool,
+	pub posts: BTreeMap<i32, Post>,
+}
+
+fn pv_source(post_id: i32, pv: &Pv) -> ChartSource {
+	match pv.post {
+		Some(post) if post == post_id => ChartSource::Post,
+		Some(post) => ChartSource::OtherPost(post),
+		None => ChartSource::BaseGame,
+	}
+}
+
+fn compatibility_chart(
+	level: Option<pv_db::Level>,
+	nc_chart: Option<&MeilisearchNcChart>,
+	pv_source: ChartSource,
+) -> Option<CompatibilityChart> {
+	let source = if nc_chart.is_some_and(|chart| chart.level.is_some()) {
+		ChartSource::Post
+	} else {
+		pv_source
+	};
+
+	level.map(|level| CompatibilityChart {
+		level: Some(level),
+		source,
+	})
+}
+
+/// Works out for every song a post touches which chart styles exist and what they depend on
+pub fn nc_compatibility(post_id: i32, pvs: &PvSearch, nc_songs: &NcSongSearch) -> NcCompatibility {
+	let pv_ids = pvs
+		.pvs
+		.iter()
+		.map(|pv| pv.id)
+		.chain(nc_songs.nc_songs.iter().map(|nc_song| nc_song.pv_id))
+		.collect::<BTreeSet<_>>();
+
+	let mut songs = Vec::new();
+	for pv_id in pv_ids {
+		let nc_song = nc_songs
+			.nc_songs
+			.iter()
+			.find(|nc_song| nc_song.post == post_id && nc_song.pv_id == pv_id);
+
+		// Prefer the posts own pv_db entry, then the base game, then any other post
+		let pv = pvs.pvs.iter().find(|pv| pv.id == pv_id).or_else(|| {
+			let others = nc_songs.pvs.get(&pv_id)?;
+			others
+				.iter()
+				.find(|pv| pv.post == Some(post_id))
+				.or_else(|| others.iter().find(|pv| pv.post.is_none()))
+				.or_else(|| others.first())
+		});
+
+		let source = pv.map(|pv| pv_source(post_id, pv));
+		let mut difficulties: [CompatibilityDifficulty; 5] = Default::default();
+
+		if let (Some(pv), Some(source)) = (pv, source) {
+			for (i, difficulty) in difficulties.iter_mut().enumerate() {
+				let Some(nc_song) = nc_song else {
+					difficulty.arcade = compatibility_chart(pv.levels[i].clone(), None, source);
+					continue;
+				};
+
+				let nc_difficulty = nc_song.difficulties[i].as_ref();
+				difficulty.arcade = compatibility_chart(
+					nc_song.get_arcade_level(pv, i),
+					nc_difficulty.and_then(|diff| diff.arcade.as_ref()),
+					source,
+				);
+				difficulty.console = compatibility_chart(
+					nc_song.get_console_level(pv, i),
+					nc_difficulty.and_then(|diff| diff.console.as_ref()),
+					source,
+				);
+				difficulty.mixed = compatibility_chart(
+					nc_song.get_mixed_level(pv, i),
+					nc_difficulty.and_then(|diff| diff.mixed.as_ref()),
+					source,
+				);
+			}
+		}
+
+		let works_without_nc = nc_song.is_none()
+			|| (source == Some(ChartSource::Post)
+				&& difficulties
+					.iter()
+					.any(|difficulty| difficulty.arcade.is_some()));
+
+		songs.push(CompatibilitySong {
+			pv_id,
+			name: pv.map(|pv| pv.name_en.clone()),
+			pv_source: source,
+			difficulties,
+			has_nc_chart: nc_song.is_some(),
+			works_without_nc,
+		});
+	}
+
+	let posts = nc_songs
+		.posts
+		.iter()
+		.filter(|(id, _)| {
+			songs
+				.iter()
+				.any(|song| song.pv_source == Some(ChartSource::OtherPost(**id)))
+		})
+		.map(|(id, post)| (*id, post.clone()))
+		.collect();
+
+	NcCompatibility {
+		post_id,
+		works_without_nc: songs.iter().all(|song| song.works_without_nc),
+		songs,
+		posts,
+	}
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/posts/{id}/nc_compatibility",
+	params(
+		("id" = i32, Path)
+	),
+	responses(
+		(status = 200, body = NcCompatibility, content_type = "application/json"),
+		(status = 401),
+		(status = 404)
+	)
+)]
+pub async fn nc_compatibility_report(
+	Path(id): Path<i32>,
+	user: Result<User, ErrorTemplate>,
+	State(state): State<AppState>,
+) -> Result<Json<NcCompatibility>, StatusCode> {
+	let Some(post) = Post::get_short(id, &state.db).await else {
+		return Err(StatusCode::NOT_FOUND);
+	};
+
+	if post.private {
+		let Ok(user) = user else {
+			return Err(StatusCode::UNAUTHORIZED);
+		};
+		if !post.authors.contains(&user) && !user.is_admin(&state.config) {
+			return Err(StatusCode::UNAUTHORIZED);
+		}
+	}
+
+	let Json(pvs) = search_pvs(
+		Query(SearchParams {
+			query: None,
+			filter: Some(format!("post={id}")),
+			limit: Some(u32::MAX as usize),
+			offset: Some(0),
+		}),
+		Query(PvFilters::default()),
+		State(state.clone()),
+	)
+	.await
+	.map_err(|(status, _)| status)?;
+
+	let Json(nc_songs) = search_nc_songs(
+		Query(SearchParams {
+			query: None,
+			filter: Some(format!("post_id={id}")),
+			limit: Some(u32::MAX as usize),
+			offset: Some(0),
+		}),
+		State(state.clone()),
+	)
+	.await
+	.map_err(|(status, _)| status)?;
+
+	Ok(Json(nc_compatibility(id, &pvs, &nc_songs)))
+}
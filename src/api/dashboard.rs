@@ -0,0 +1,276 @@
+use crate::AppState;
+use crate::api::downloads::*;
+use crate::models::*;
+use axum::{extract::*, http::StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::*;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct ActivityPoint {
+	/// The first day of the interval
+	pub date: time::Date,
+	pub downloads: i64,
+	pub likes: i64,
+	pub comments: i64,
+}
+
+impl ActivityPoint {
+	fn new(date: time::Date) -> Self {
+		Self {
+			date,
+			downloads: 0,
+			likes: 0,
+			comments: 0,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct VariantDownloads {
+	pub variant: i32,
+	pub file: String,
+	/// Downloads in the last year, older download events are pruned
+	pub downloads: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct PostAnalytics {
+	pub post: Post,
+	pub activity: Vec<ActivityPoint>,
+	pub downloads: i64,
+	pub likes: i64,
+	pub comments: i64,
+	/// Sorted by most downloaded first
+	pub variants: Vec<VariantDownloads>,
+	/// How many other posts list this one as a dependency
+	pub dependents: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct AuthorDashboard {
+	pub interval: DownloadInterval,
+	pub days: i32,
+	/// Activity of every post combined
+	pub activity: Vec<ActivityPoint>,
+	pub posts: Vec<PostAnalytics>,
+}
+
+impl AuthorDashboard {
+	pub fn peak_downloads(&self) -> i64 {
+		self.activity
+			.iter()
+			.map(|point| point.downloads)
+			.max()
+			.unwrap_or(0)
+			.max(1)
+	}
+}
+
+fn add_activity(
+	activity: &mut BTreeMap<i32, BTreeMap<time::Date, ActivityPoint>>,
+	post_id: i32,
+	date: time::Date,
+) -> &mut ActivityPoint {
+	activity
+		.entry(post_id)
+		.or_default()
+		.entry(date)
+		.or_insert_with(|| ActivityPoint::new(date))
+}
+
+/// Collects the analytics of every post the author has, including private ones
+pub async fn author_dashboard(
+	author_id: i64,
+	params: &DownloadStatsParams,
+	state: &AppState,
+) -> Result<AuthorDashboard, StatusCode> {
+	let interval = params.interval.unwrap_or_default();
+	let days = params.days();
+
+	let ids = sqlx::query!(
+		"SELECT post_id FROM post_authors WHERE user_id = $1",
+		author_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+	.into_iter()
+	.map(|post| post.post_id)
+	.collect::<Vec<_>>();
+
+	let mut posts = Post::get_many_short(&ids, &state.db).await;
+	posts.sort_by(|a, b| b.time.cmp(&a.time));
+
+	let mut activity = BTreeMap::new();
+
+	let downloads = sqlx::query!(
+		r#"
+		SELECT post_id, date_trunc($2, day)::date AS "date!", COUNT(*) AS "count!"
+		FROM download_events
+		WHERE post_id = ANY($1) AND day > CURRENT_DATE - $3::int
+		GROUP BY 1, 2
+		"#,
+		&ids,
+		interval.date_trunc(),
+		days
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default();
+	for point in downloads {
+		add_activity(&mut activity, point.post_id, point.date).downloads = point.count;
+	}
+
+	let likes = sqlx::query!(
+		r#"
+		SELECT post_id, date_trunc($2, time)::date AS "date!", COUNT(*) AS "count!"
+		FROM liked_posts
+		WHERE post_id = ANY($1) AND time > CURRENT_DATE - $3::int
+		GROUP BY 1, 2
+		"#,
+		&ids,
+		interval.date_trunc(),
+		days
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default();
+	for point in likes {
+		add_activity(&mut activity, point.post_id, point.date).likes = point.count;
+	}
+
+	let comments = sqlx::query!(
+		r#"
+		SELECT post_id, date_trunc($2, time)::date AS "date!", COUNT(*) AS "count!"
+		FROM post_comments
+		WHERE post_id = ANY($1) AND time > CURRENT_DATE - $3::int
+		GROUP BY 1, 2
+		"#,
+		&ids,
+		interval.date_trunc(),
+		days
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default();
+	for point in comments {
+		add_activity(&mut activity, point.post_id, point.date).comments = point.count;
+	}
+
+	let mut variants: BTreeMap<i32, Vec<(i32, i64)>> = BTreeMap::new();
+	for variant in sqlx::query!(
+		r#"
+		SELECT post_id, variant, COUNT(*) AS "count!"
+		FROM download_events
+		WHERE post_id = ANY($1)
+		GROUP BY 1, 2
+		"#,
+		&ids
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default()
+	{
+		variants
+			.entry(variant.post_id)
+			.or_default()
+			.push((variant.variant, variant.count));
+	}
+
+	let dependents = sqlx::query!(
+		r#"
+		SELECT dependency_id, COUNT(*) AS "count!"
+		FROM post_dependencies
+		WHERE dependency_id = ANY($1)
+		GROUP BY 1
+		"#,
+		&ids
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default()
+	.into_iter()
+	.map(|dependent| (dependent.dependency_id, dependent.count))
+	.collect::<BTreeMap<_, _>>();
+
+	let mut total_activity: BTreeMap<time::Date, ActivityPoint> = BTreeMap::new();
+	let posts = posts
+		.into_iter()
+		.map(|post| {
+			let activity = activity
+				.remove(&post.id)
+				.unwrap_or_default()
+				.into_values()
+				.collect::<Vec<_>>();
+			for point in &activity {
+				let total = total_activity
+					.entry(point.date)
+					.or_insert_with(|| ActivityPoint::new(point.date));
+				total.downloads += point.downloads;
+				total.likes += point.likes;
+				total.comments += point.comments;
+			}
+
+			let mut variants = variants
+				.remove(&post.id)
+				.unwrap_or_default()
+				.into_iter()
+				.map(|(variant, downloads)| VariantDownloads {
+					variant,
+					file: post
+						.local_files
+						.get(variant as usize)
+						.and_then(|file| file.rsplit('/').next())
+						.unwrap_or_default()
+						.to_string(),
+					downloads,
+				})
+				.collect::<Vec<_>>();
+			variants.sort_by(|a, b| b.downloads.cmp(&a.downloads));
+
+			PostAnalytics {
+				downloads: activity.iter().map(|point| point.downloads).sum(),
+				likes: activity.iter().map(|point| point.likes).sum(),
+				comments: activity.iter().map(|point| point.comments).sum(),
+				dependents: dependents.get(&post.id).copied().unwrap_or(0),
+				activity,
+				variants,
+				post,
+			}
+		})
+		.collect();
+
+	Ok(AuthorDashboard {
+		interval,
+		days,
+		activity: total_activity.into_values().collect(),
+		posts,
+	})
+}
+
+/// Per post downloads, likes and comments over time for the authors own posts, only available to the author and admins
+#[utoipa::path(
+	get,
+	path = "/api/v1/users/{id}/dashboard",
+	params(
+		("id" = i64, Path),
+		DownloadStatsParams
+	),
+	responses(
+		(status = 200, body = AuthorDashboard, content_type = "application/json"),
+		(status = 401)
+	)
+)]
+pub async fn dashboard(
+	Path(id): Path<i64>,
+	Query(params): Query<DownloadStatsParams>,
+	user: User,
+	State(state): State<AppState>,
+) -> Result<Json<AuthorDashboard>, StatusCode> {
+	if user.id != id && !user.is_admin(&state.config) {
+		return Err(StatusCode::UNAUTHORIZED);
+	}
+
+	Ok(Json(author_dashboard(id, &params, &state).await?))
+}
@@ -0,0 +1,336 @@
+use crate::AppState;
+use crate::models::*;
+use axum::{
+	extract::*,
+	http::{HeaderMap, StatusCode, header},
+};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::net::{IpAddr, SocketAddr};
+use utoipa::{IntoParams, ToSchema};
+
+/// Repeat downloads of the same file by the same client within this many hours are only counted once
+const DEDUP_WINDOW_HOURS: i32 = 24;
+/// How many days of downloads the trending sort looks at
+const TRENDING_DAYS: i32 = 7;
+const MAX_DAYS: i32 = 365;
+
+const BOT_USER_AGENTS: &[&str] = &["bot", "crawler", "spider", "slurp", "curl", "wget"];
+
+/// A block of addresses in cidr notation, a bare address is a block of one
+#[derive(Clone, Copy)]
+pub struct IpRange {
+	addr: IpAddr,
+	prefix: u32,
+}
+
+impl IpRange {
+	pub fn parse(range: &str) -> Option<Self> {
+		let (addr, prefix) = match range.trim().split_once('/') {
+			Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, Some(prefix.parse().ok()?)),
+			None => (range.trim().parse::<IpAddr>().ok()?, None),
+		};
+		let bits = Self::bits(addr).1;
+		let prefix = prefix.unwrap_or(bits);
+		if prefix > bits {
+			return None;
+		}
+		Some(Self { addr, prefix })
+	}
+
+	fn bits(addr: IpAddr) -> (u128, u32) {
+		match addr.to_canonical() {
+			IpAddr::V4(addr) => (u32::from(addr) as u128, 32),
+			IpAddr::V6(addr) => (u128::from(addr), 128),
+		}
+	}
+
+	pub fn contains(&self, addr: IpAddr) -> bool {
+		let (range, range_bits) = Self::bits(self.addr);
+		let (addr, addr_bits) = Self::bits(addr);
+		range_bits == addr_bits
+			&& (self.prefix == 0 || (range ^ addr) >> (range_bits - self.prefix) == 0)
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadInterval {
+	#[default]
+	Daily,
+	Weekly,
+}
+
+impl DownloadInterval {
+	pub fn date_trunc(&self) -> &'static str {
+		match self {
+			Self::Daily => "day",
+			Self::Weekly => "week",
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, IntoParams)]
+pub struct DownloadStatsParams {
+	/// Whether each point covers a day or a week, defaults to daily
+	pub interval: Option<DownloadInterval>,
+	/// How many days back to look, at most 365, defaults to 30
+	pub days: Option<i32>,
+}
+
+impl DownloadStatsParams {
+	pub fn days(&self) -> i32 {
+		self.days.unwrap_or(30).clamp(1, MAX_DAYS)
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct DownloadPoint {
+	/// The first day of the interval
+	pub date: time::Date,
+	pub downloads: i64,
+}
+
+/// Identifies a downloader without storing their ip, returns none for known bots
+/// Mod managers often send no user agent at all, so those are still counted
+pub fn client_id(
+	user: Option<&User>,
+	headers: &HeaderMap,
+	addr: SocketAddr,
+	state: &AppState,
+) -> Option<String> {
+	let user_agent = headers
+		.get(header::USER_AGENT)
+		.and_then(|value| value.to_str().ok())
+		.unwrap_or_default();
+	let lowercase = user_agent.to_lowercase();
+	if BOT_USER_AGENTS.iter().any(|bot| lowercase.contains(bot)) {
+		return None;
+	}
+
+	let client = if let Some(user) = user {
+		format!("user:{}", user.id)
+	} else {
+		// Cloudflare's header is only trusted when the connection comes from a configured proxy,
+		// otherwise any client could set it to get counted again
+		let trusted = state
+			.config
+			.trusted_proxies
+			.iter()
+			.any(|range| range.contains(addr.ip()));
+		let ip = headers
+			.get("cf-connecting-ip")
+			.filter(|_| trusted)
+			.and_then(|value| value.to_str().ok())
+			.and_then(|value| value.trim().parse::<IpAddr>().ok())
+			.unwrap_or(addr.ip());
+		format!("ip:{ip}:{user_agent}")
+	};
+
+	let mut hasher = sha2::Sha256::new();
+	hasher.update(state.config.download_salt.as_bytes());
+	hasher.update(client.as_bytes());
+	Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Records a download unless the client already downloaded the same file recently, returns if it was counted
+/// The claim row is locked by the upsert, so parallel requests for the same file only count once
+pub async fn record_download(post_id: i32, variant: i32, client: &str, state: &AppState) -> bool {
+	sqlx::query!(
+		r#"
+		WITH claim AS (
+			INSERT INTO download_claims (client, post_id, variant)
+			VALUES ($3, $1, $2)
+			ON CONFLICT (client, post_id, variant) DO UPDATE SET time = CURRENT_TIMESTAMP
+			WHERE download_claims.time <= CURRENT_TIMESTAMP - make_interval(hours => $4)
+			RETURNING 1
+		)
+		INSERT INTO download_events (post_id, variant, client)
+		SELECT $1, $2, $3 FROM claim
+		"#,
+		post_id,
+		variant,
+		client,
+		DEDUP_WINDOW_HOURS
+	)
+	.execute(&state.db)
+	.await
+	.is_ok_and(|result| result.rows_affected() > 0)
+}
+
+/// Drops download events older than the stats go back and claims older than the dedup window
+pub async fn prune_download_events(state: &AppState) {
+	_ = sqlx::query!(
+		"DELETE FROM download_events WHERE day < CURRENT_DATE - $1::int",
+		MAX_DAYS
+	)
+	.execute(&state.db)
+	.await;
+
+	_ = sqlx::query!(
+		"DELETE FROM download_claims WHERE time <= CURRENT_TIMESTAMP - make_interval(hours => $1)",
+		DEDUP_WINDOW_HOURS
+	)
+	.execute(&state.db)
+	.await;
+}
+
+#[derive(Serialize)]
+struct TrendingDocument {
+	id: i32,
+	trending: i64,
+}
+
+/// Updates the trending attribute of every post in meilisearch to its recent deduplicated downloads
+pub async fn update_trending(state: &AppState) {
+	let Ok(posts) = sqlx::query!(
+		r#"
+		SELECT p.id, COUNT(e.post_id) AS "trending!"
+		FROM posts p
+		LEFT JOIN download_events e ON e.post_id = p.id AND e.day > CURRENT_DATE - $1::int
+		GROUP BY p.id
+		"#,
+		TRENDING_DAYS
+	)
+	.fetch_all(&state.db)
+	.await
+	else {
+		return;
+	};
+
+	let documents = posts
+		.into_iter()
+		.map(|post| TrendingDocument {
+			id: post.id,
+			trending: post.trending,
+		})
+		.collect::<Vec<_>>();
+
+	// Partial documents, the rest of each post is left as is
+	_ = state
+		.meilisearch
+		.index("posts")
+		.add_or_update(&documents, Some("id"))
+		.await;
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/posts/{id}/downloads",
+	params(
+		("id" = i32, Path),
+		DownloadStatsParams
+	),
+	responses(
+		(status = 200, body = Vec<DownloadPoint>, content_type = "application/json"),
+		(status = 401),
+		(status = 404)
+	)
+)]
+pub async fn post_downloads(
+	Path(id): Path<i32>,
+	Query(params): Query<DownloadStatsParams>,
+	user: Result<User, ErrorTemplate>,
+	State(state): State<AppState>,
+) -> Result<Json<Vec<DownloadPoint>>, StatusCode> {
+	let Some(post) = Post::get_short(id, &state.db).await else {
+		return Err(StatusCode::NOT_FOUND);
+	};
+
+	if post.private {
+		let Ok(user) = user else {
+			return Err(StatusCode::UNAUTHORIZED);
+		};
+		if !post.authors.contains(&user) && !user.is_admin(&state.config) {
+			return Err(StatusCode::UNAUTHORIZED);
+		}
+	}
+
+	let interval = params.interval.unwrap_or_default();
+	let days = params.days();
+
+	let points = sqlx::query!(
+		r#"
+		SELECT date_trunc($2, day)::date AS "date!", COUNT(*) AS "downloads!"
+		FROM download_events
+		WHERE post_id = $1 AND day > CURRENT_DATE - $3::int
+		GROUP BY 1
+		ORDER BY 1
+		"#,
+		post.id,
+		interval.date_trunc(),
+		days
+	)
+	.fetch_all(&state.db)
+	.await
+	.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	Ok(Json(
+		points
+			.into_iter()
+			.map(|point| DownloadPoint {
+				date: point.date,
+				downloads: point.downloads,
+			})
+			.collect(),
+	))
+}
+
+/// Downloads of every post the user is an author of, private posts are only included for the user themselves
+#[utoipa::path(
+	get,
+	path = "/api/v1/users/{id}/downloads",
+	params(
+		("id" = i64, Path),
+		DownloadStatsParams
+	),
+	responses(
+		(status = 200, body = Vec<DownloadPoint>, content_type = "application/json"),
+		(status = 404)
+	)
+)]
+pub async fn author_downloads(
+	Path(id): Path<i64>,
+	Query(params): Query<DownloadStatsParams>,
+	user: Result<User, ErrorTemplate>,
+	State(state): State<AppState>,
+) -> Result<Json<Vec<DownloadPoint>>, StatusCode> {
+	let Some(author) = User::get(id, &state.db).await else {
+		return Err(StatusCode::NOT_FOUND);
+	};
+
+	let include_private = user
+		.as_ref()
+		.is_ok_and(|user| user.id == author.id || user.is_admin(&state.config));
+	let interval = params.interval.unwrap_or_default();
+	let days = params.days();
+
+	let points = sqlx::query!(
+		r#"
+		SELECT date_trunc($2, e.day)::date AS "date!", COUNT(*) AS "downloads!"
+		FROM download_events e
+		JOIN post_authors pa ON pa.post_id = e.post_id
+		JOIN posts p ON p.id = e.post_id
+		WHERE pa.user_id = $1 AND e.day > CURRENT_DATE - $3::int AND (p.private = false OR $4)
+		GROUP BY 1
+		ORDER BY 1
+		"#,
+		author.id,
+		interval.date_trunc(),
+		days,
+		include_private
+	)
+	.fetch_all(&state.db)
+	.await
+	.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	Ok(Json(
+		points
+			.into_iter()
+			.map(|point| DownloadPoint {
+				date: point.date,
+				downloads: point.downloads,
+			})
+			.collect(),
+	))
+}
@@ -15,9 +15,9 @@ pub struct SearchParams {
 	/**
 	A meilisearch filter, such as `post != 100`
 	Attributes depend on search
-	PVs: post, pv_id
+	PVs: post, pv_id, song_keys, levels, charts.found, charts.total_notes, charts.duration, metadata.bpm, metadata.performers, metadata.lyrics, metadata.lyrics_en, metadata.stars, metadata.difficulties.edition, metadata.difficulties.original, metadata.difficulties.extra, metadata.difficulties.slide
 	Modules: post_id, module_id, chara, cos.id, cos.items.id
-	Cstm_items: post_id, customize_item_id
+	Cstm_items: post_id, customize_item_id, bind_module
 	NC Songs: post_id, pv_id
 	Sprites/Aets/Objsets/Textures: post_id, id, name
 	*/
@@ -26,6 +26,54 @@ pub struct SearchParams {
 	pub offset: Option<usize>,
 }
 
+/// Typed pv filters, combined with `filter` using AND
+#[derive(Serialize, Deserialize, Clone, Default, IntoParams)]
+pub struct PvFilters {
+	/// Matches pvs with any difficulty in the level range, such as 7.5
+	pub min_level: Option<f32>,
+	pub max_level: Option<f32>,
+	/// Character code of a performer, such as MIK
+	pub performer: Option<String>,
+	pub min_bpm: Option<i32>,
+	pub max_bpm: Option<i32>,
+}
+
+impl PvFilters {
+	fn range<T: std::fmt::Display>(
+		attribute: &str,
+		min: Option<T>,
+		max: Option<T>,
+	) -> Option<String> {
+		match (min, max) {
+			(Some(min), Some(max)) => Some(format!("{attribute} {min} TO {max}")),
+			(Some(min), None) => Some(format!("{attribute} >= {min}")),
+			(None, Some(max)) => Some(format!("{attribute} <= {max}")),
+			(None, None) => None,
+		}
+	}
+
+	pub fn filters(&self) -> Vec<String> {
+		let mut filters = Vec::new();
+		filters.extend(Self::range(
+			"metadata.stars",
+			self.min_level,
+			self.max_level,
+		));
+		filters.extend(Self::range("metadata.bpm", self.min_bpm, self.max_bpm));
+		if let Some(performer) = &self.performer {
+			let performer = performer
+				.chars()
+				.filter(char::is_ascii_alphanumeric)
+				.collect::<String>()
+				.to_uppercase();
+			if !performer.is_empty() {
+				filters.push(format!("metadata.performers = {performer}"));
+			}
+		}
+		filters
+	}
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct MeilisearchPv {
 	pub uid: u64,
@@ -36,6 +84,52 @@ pub struct MeilisearchPv {
 	pub song_info: Option<pv_db::SongInfo>,
 	pub song_info_en: Option<pv_db::SongInfo>,
 	pub levels: [Option<pv_db::Level>; 5],
+	#[serde(default)]
+	pub charts: [Option<crate::dsc::Chart>; 5],
+	#[serde(default)]
+	pub metadata: PvMetadata,
+	#[serde(default)]
+	pub preview: bool,
+	#[serde(default)]
+	pub song_keys: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, ToSchema)]
+#[serde(default)]
+pub struct PvMetadata {
+	pub bpm: Option<i32>,
+	/// Character codes of the performers, such as MIK
+	pub performers: Vec<String>,
+	pub sabi: Option<PvSabi>,
+	pub lyrics: bool,
+	pub lyrics_en: bool,
+	/// Numeric level of each difficulty, such as 7.5
+	pub stars: [Option<f32>; 5],
+	pub difficulties: [Option<DifficultyAttributes>; 5],
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct PvSabi {
+	pub start_time: f32,
+	pub play_time: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct DifficultyAttributes {
+	pub edition: i32,
+	pub original: bool,
+	pub extra: bool,
+	pub slide: bool,
+}
+
+impl MeilisearchPv {
+	pub fn preview_url(&self) -> Option<String> {
+		if self.preview {
+			Some(crate::previews::preview_url(self.post, self.pv_id))
+		} else {
+			None
+		}
+	}
 }
 
 #[derive(Serialize, Deserialize)]
@@ -154,73 +248,196 @@ pub const DB_PREFIXES: [&'static str; 21] = [
 ];
 
 pub async fn extract_post_data(post_id: i32, state: AppState) -> Option<()> {
-	let post = Post::get_short(post_id, &state.db).await?;
-	if post.post_type == PostType::Cover {
-		return None;
-	}
+	let before = crate::api::changes::extracted_ids(post_id, &state).await;
+	let extracted = extract_post_files(post_id, state.clone()).await;
+	crate::api::changes::log_extraction_changes(post_id, before, &state).await;
+	optimise_all_reservations(&state).await;
+	crate::api::lint::update_lint_report(post_id, &state).await;
+	extracted
+}
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("pvs"))
-		.with_filter(&format!("post={}", post.id))
-		.execute::<MeilisearchPv>()
-		.await;
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct ReextractProgress {
+	pub total: usize,
+	pub completed: usize,
+	pub failed: Vec<i32>,
+	pub running: bool,
+	#[serde(with = "time::serde::rfc3339")]
+	pub started: time::OffsetDateTime,
+}
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("modules"))
-		.with_filter(&format!("post_id={}", post.id))
-		.execute::<MeilisearchModule>()
-		.await;
+#[derive(Serialize, Deserialize, Default, ToSchema)]
+pub struct ReextractArgs {
+	/// Only re-extract these posts, every post is re-extracted when empty
+	#[serde(default)]
+	pub post_ids: Vec<i32>,
+	pub post_type: Option<i32>,
+	/// How many posts to extract at once, defaults to 4
+	pub concurrency: Option<usize>,
+	/// Delete every meilisearch index and recreate its settings before extracting
+	#[serde(default)]
+	pub reset_indexes: bool,
+}
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("cstm_items"),
+pub async fn find_reextract_posts(args: &ReextractArgs, state: &AppState) -> Vec<i32> {
+	sqlx::query!(
+		"SELECT id FROM posts WHERE type != $1 AND (cardinality($2::int[]) = 0 OR id = ANY($2)) AND ($3::int IS NULL OR type = $3) ORDER BY id",
+		PostType::Cover as i32,
+		&args.post_ids,
+		args.post_type
 	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchCstmItem>()
-	.await;
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default()
+	.into_iter()
+	.map(|post| post.id)
+	.collect()
+}
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("nc_songs"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchNcSong>()
-	.await;
+// Re-runs extraction for every given post, at most concurrency at a time, reporting progress in state.reextract_progress
+pub async fn reextract_posts(post_ids: Vec<i32>, concurrency: usize, state: AppState) {
+	let total = post_ids.len();
+	*state.reextract_progress.lock().await = Some(ReextractProgress {
+		total,
+		completed: 0,
+		failed: Vec::new(),
+		running: true,
+		started: time::OffsetDateTime::now_utc(),
+	});
+
+	let mut post_ids = post_ids.into_iter();
+	let mut set = tokio::task::JoinSet::new();
+	loop {
+		while set.len() < concurrency.max(1) {
+			let Some(post_id) = post_ids.next() else {
+				break;
+			};
+			let state = state.clone();
+			set.spawn(async move {
+				let before = crate::api::changes::extracted_ids(post_id, &state).await;
+				let extracted = extract_post_files(post_id, state.clone()).await;
+				crate::api::changes::log_extraction_changes(post_id, before, &state).await;
+				if extracted.is_some() {
+					crate::api::lint::update_lint_report(post_id, &state).await;
+				}
+				(post_id, extracted)
+			});
+		}
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("sprite_sets"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchDbEntry>()
-	.await;
+		let Some(result) = set.join_next().await else {
+			break;
+		};
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("sprites"))
-		.with_filter(&format!("post_id={}", post.id))
-		.execute::<MeilisearchDbEntry>()
-		.await;
+		let mut progress = state.reextract_progress.lock().await;
+		let Some(progress) = progress.as_mut() else {
+			continue;
+		};
+		progress.completed += 1;
+		match result {
+			Ok((post_id, Some(()))) => {
+				log::info!("Extracted post {post_id} ({}/{total})", progress.completed);
+			}
+			Ok((post_id, None)) => {
+				log::warn!(
+					"Failed to extract post {post_id} ({}/{total})",
+					progress.completed
+				);
+				progress.failed.push(post_id);
+			}
+			Err(e) => {
+				log::warn!(
+					"Extraction task failed: {e} ({}/{total})",
+					progress.completed
+				);
+			}
+		}
+	}
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("aet_sets"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchDbEntry>()
-	.await;
+	optimise_all_reservations(&state).await;
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("aet_scenes"),
+	if let Some(progress) = state.reextract_progress.lock().await.as_mut() {
+		progress.running = false;
+	}
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/ids/reextract",
+	request_body = ReextractArgs,
+	responses(
+		(status = 200, body = ReextractProgress, content_type = "application/json"),
+		(status = 401),
+		(status = 409)
 	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchDbEntry>()
-	.await;
+)]
+pub async fn start_reextract(
+	user: User,
+	State(state): State<AppState>,
+	Json(args): Json<ReextractArgs>,
+) -> Result<Json<ReextractProgress>, StatusCode> {
+	if !user.is_admin(&state.config) {
+		return Err(StatusCode::UNAUTHORIZED);
+	}
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("objsets"))
-		.with_filter(&format!("post_id={}", post.id))
-		.execute::<MeilisearchDbEntry>()
-		.await;
+	// The lock is held until the new progress is stored so two requests can't both start
+	let mut current = state.reextract_progress.lock().await;
+	if current.as_ref().is_some_and(|progress| progress.running) {
+		return Err(StatusCode::CONFLICT);
+	}
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("textures"),
+	let post_ids = find_reextract_posts(&args, &state).await;
+	let progress = ReextractProgress {
+		total: post_ids.len(),
+		completed: 0,
+		failed: Vec::new(),
+		running: true,
+		started: time::OffsetDateTime::now_utc(),
+	};
+	*current = Some(progress.clone());
+	drop(current);
+
+	tokio::spawn(async move {
+		if args.reset_indexes {
+			crate::reset_indexes(&state).await;
+		}
+		reextract_posts(post_ids, args.concurrency.unwrap_or(4), state).await;
+	});
+
+	Ok(Json(progress))
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/ids/reextract",
+	responses(
+		(status = 200, body = Option<ReextractProgress>, content_type = "application/json"),
+		(status = 401)
 	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchDbEntry>()
-	.await;
+)]
+pub async fn reextract_progress(
+	user: User,
+	State(state): State<AppState>,
+) -> Result<Json<Option<ReextractProgress>>, StatusCode> {
+	if !user.is_admin(&state.config) {
+		return Err(StatusCode::UNAUTHORIZED);
+	}
+
+	Ok(Json(state.reextract_progress.lock().await.clone()))
+}
+
+async fn extract_post_files(post_id: i32, state: AppState) -> Option<()> {
+	let post = Post::get_short(post_id, &state.db).await?;
+	if post.post_type == PostType::Cover {
+		return None;
+	}
 
+	delete_extracted_data(post.id, &state).await;
+
+	_ = sqlx::query!("DELETE FROM post_mod_names WHERE post_id = $1", post.id)
+		.execute(&state.db)
+		.await;
+
+	let mut archive = crate::api::lint::ArchiveContents::default();
 	for file in &post.local_files {
 		let file = format!("{}/{file}", state.config.storage_path);
 		let file = Path::new(&file);
@@ -244,6 +461,15 @@ pub async fn extract_post_data(post_id: i32, state: AppState) -> Option<()> {
 			let file = file.path();
 			let data = tokio::fs::read_to_string(file).await.ok()?;
 			let config = data.parse::<toml::Table>().ok()?;
+			if let Some(toml::Value::String(name)) = config.get("name") {
+				_ = sqlx::query!(
+					"INSERT INTO post_mod_names (post_id, name) VALUES ($1, $2)",
+					post.id,
+					name.trim()
+				)
+				.execute(&state.db)
+				.await;
+			}
 			let Some(toml::Value::Array(includes)) = config.get("include") else {
 				continue;
 			};
@@ -268,111 +494,632 @@ pub async fn extract_post_data(post_id: i32, state: AppState) -> Option<()> {
 				}
 			}
 
+			let mod_folder = file.parent()?.strip_prefix(dir).ok()?;
 			for include in &dirs {
-				for rom in &ROM_DIRS {
-					let folder = format!("{}/{include}/{rom}/rom", file.parent()?.to_str()?);
-					let path = Path::new(&folder);
-					if !path.exists() {
-						continue;
-					}
+				let folder = mod_folder.join(include);
+				extract_rom_dirs(
+					&format!("{}/{include}", file.parent()?.to_str()?),
+					folder.to_str()?,
+					post_id,
+					&state,
+					&mut archive,
+				)
+				.await;
+			}
+		}
 
-					let nc_db = format!("{folder}/nc_db.toml");
-					let path = Path::new(&nc_db);
-					if path.exists() {
-						if let Ok(data) = tokio::fs::read_to_string(&path).await {
-							parse_nc_db(&data, post_id, state.clone()).await;
-						}
-					}
+		crate::thumbnails::render_thumbnails(post.id, &archive, &state).await;
+	}
 
-					for prefix in &DB_PREFIXES {
-						let pv_db = format!("{folder}/{prefix}pv_db.txt");
-						let path = Path::new(&pv_db);
-						if path.exists() {
-							if let Ok(data) = tokio::fs::read_to_string(&path).await {
-								parse_pv_db(&data, post_id, state.clone()).await;
-							}
-						}
+	let lints = crate::api::lint::lint_archive(archive, &state).await;
+	crate::api::lint::store_archive_lints(post.id, &lints, &state).await;
 
-						let pv_db = format!("{folder}/{prefix}nc_pv_db.txt");
-						let path = Path::new(&pv_db);
-						if path.exists() {
-							if let Ok(data) = tokio::fs::read_to_string(&path).await {
-								parse_pv_db(&data, post_id, state.clone()).await;
-							}
-						}
+	Some(())
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BaseGameImportArgs {
+	/// Path on the server to a game data dump containing the rom_* folders
+	pub path: String,
+	/// A label for this baseline, such as `1.03 + DLC`
+	pub version: String,
+	/// Remove the existing baseline first, otherwise the dump is merged into it
+	#[serde(default)]
+	pub replace: bool,
+	/// Re-extract every post afterwards instead of only the ones sharing ids with the imported entries
+	#[serde(default)]
+	pub reextract_all: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BaseGameImport {
+	pub id: i32,
+	pub version: String,
+	pub replaced: bool,
+	pub rolled_back: bool,
+	pub pvs: i64,
+	pub modules: i64,
+	pub cstm_items: i64,
+	pub db_entries: i64,
+	#[serde(with = "time::serde::rfc3339")]
+	pub time: time::OffsetDateTime,
+}
+
+// Base game entries are stored with a post_id of -1, extraction of posts skips anything identical to them
+// Every entry the import adds or changes is tagged with its id so it can be rolled back later
+pub async fn import_base_game(
+	args: &BaseGameImportArgs,
+	state: &AppState,
+) -> Option<BaseGameImport> {
+	if !Path::new(&args.path).is_dir() {
+		return None;
+	}
+
+	let now = time::OffsetDateTime::now_utc();
+	let time = time::PrimitiveDateTime::new(now.date(), now.time());
+
+	let import_id = sqlx::query!(
+		r#"
+		INSERT INTO base_game_imports (version, replaced, path, pvs, modules, cstm_items, db_entries, time)
+		VALUES ($1, $2, $3, 0, 0, 0, 0, $4)
+		RETURNING id
+		"#,
+		args.version,
+		args.replace,
+		args.path,
+		time
+	)
+	.fetch_one(&state.db)
+	.await
+	.ok()?
+	.id;
+
+	if args.replace {
+		delete_extracted_data(-1, state).await;
+	}
+
+	apply_base_game_dump(&args.path, import_id, state).await;
+	optimise_all_reservations(state).await;
+
+	let import = sqlx::query!(
+		r#"
+		UPDATE base_game_imports SET
+			pvs = (SELECT COUNT(*) FROM pvs WHERE post_id = -1),
+			modules = (SELECT COUNT(*) FROM modules WHERE post_id = -1),
+			cstm_items = (SELECT COUNT(*) FROM cstm_items WHERE post_id = -1),
+			db_entries = (SELECT COUNT(*) FROM db_entries WHERE post_id = -1)
+		WHERE id = $1
+		RETURNING id, version, replaced, rolled_back, pvs, modules, cstm_items, db_entries, time
+		"#,
+		import_id
+	)
+	.fetch_one(&state.db)
+	.await
+	.ok()?;
+
+	let post_ids = if args.reextract_all {
+		find_reextract_posts(&ReextractArgs::default(), state).await
+	} else {
+		base_game_import_posts(import_id, state).await
+	};
+	reextract_posts(post_ids, 4, state.clone()).await;
+
+	Some(BaseGameImport {
+		id: import.id,
+		version: import.version,
+		replaced: import.replaced,
+		rolled_back: import.rolled_back,
+		pvs: import.pvs,
+		modules: import.modules,
+		cstm_items: import.cstm_items,
+		db_entries: import.db_entries,
+		time: import.time.assume_offset(time::UtcOffset::UTC),
+	})
+}
+
+async fn apply_base_game_dump(path: &str, import_id: i32, state: &AppState) {
+	let mut archive = crate::api::lint::ArchiveContents::default();
+	extract_rom_dirs(path, "", -1, state, &mut archive).await;
+	crate::thumbnails::render_thumbnails(-1, &archive, state).await;
+
+	// Storing an entry clears its import_id, so anything still untagged came from this dump
+	_ = sqlx::query!(
+		"UPDATE pvs SET import_id = $1 WHERE post_id = -1 AND import_id IS NULL",
+		import_id
+	)
+	.execute(&state.db)
+	.await;
+	_ = sqlx::query!(
+		"UPDATE modules SET import_id = $1 WHERE post_id = -1 AND import_id IS NULL",
+		import_id
+	)
+	.execute(&state.db)
+	.await;
+	_ = sqlx::query!(
+		"UPDATE cstm_items SET import_id = $1 WHERE post_id = -1 AND import_id IS NULL",
+		import_id
+	)
+	.execute(&state.db)
+	.await;
+	_ = sqlx::query!(
+		"UPDATE nc_songs SET import_id = $1 WHERE post_id = -1 AND import_id IS NULL",
+		import_id
+	)
+	.execute(&state.db)
+	.await;
+	_ = sqlx::query!(
+		"UPDATE db_entries SET import_id = $1 WHERE post_id = -1 AND import_id IS NULL",
+		import_id
+	)
+	.execute(&state.db)
+	.await;
+}
+
+// Posts with entries sharing an id with the base game entries of an import, their extraction may have been filtered against a different baseline
+async fn base_game_import_posts(import_id: i32, state: &AppState) -> Vec<i32> {
+	sqlx::query!(
+		r#"
+		SELECT p.post_id AS "post_id!" FROM pvs p
+		JOIN pvs b ON b.pv_id = p.pv_id AND b.post_id = -1 AND b.import_id = $1
+		WHERE p.post_id != -1
+		UNION
+		SELECT m.post_id FROM modules m
+		JOIN modules b ON b.module_id = m.module_id AND b.post_id = -1 AND b.import_id = $1
+		WHERE m.post_id != -1
+		UNION
+		SELECT c.post_id FROM cstm_items c
+		JOIN cstm_items b ON b.customize_item_id = c.customize_item_id AND b.post_id = -1 AND b.import_id = $1
+		WHERE c.post_id != -1
+		UNION
+		SELECT d.post_id FROM db_entries d
+		JOIN db_entries b ON b.db_type = d.db_type AND b.id = d.id AND b.post_id = -1 AND b.import_id = $1
+		WHERE d.post_id != -1
+		"#,
+		import_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default()
+	.into_iter()
+	.map(|post| post.post_id)
+	.collect()
+}
+
+// Only the latest import that hasn't been rolled back can be rolled back
+// Its entries are removed and the earlier imports since the last replacing one are applied again from their dumps
+pub async fn rollback_base_game_import(import_id: i32, state: &AppState) -> Option<()> {
+	let latest = sqlx::query!(
+		"SELECT id FROM base_game_imports WHERE rolled_back = false ORDER BY id DESC LIMIT 1"
+	)
+	.fetch_one(&state.db)
+	.await
+	.ok()?;
+	if latest.id != import_id {
+		return None;
+	}
+
+	let affected = base_game_import_posts(import_id, state).await;
+
+	sqlx::query!(
+		"UPDATE base_game_imports SET rolled_back = true WHERE id = $1",
+		import_id
+	)
+	.execute(&state.db)
+	.await
+	.ok()?;
+
+	let imports = sqlx::query!(
+		r#"
+		SELECT id, path FROM base_game_imports
+		WHERE rolled_back = false
+		AND id >= COALESCE((SELECT MAX(id) FROM base_game_imports WHERE rolled_back = false AND replaced), 0)
+		ORDER BY id
+		"#
+	)
+	.fetch_all(&state.db)
+	.await
+	.ok()?;
+
+	// Entries of imports that can't be applied again are kept as they are
+	let mut reapply = Vec::new();
+	for import in imports {
+		match import.path {
+			Some(path) if Path::new(&path).is_dir() => reapply.push((import.id, path)),
+			_ => log::warn!(
+				"Dump of base game import {} is missing, keeping its entries as they are",
+				import.id
+			),
+		}
+	}
+
+	let mut removed = reapply.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+	removed.push(import_id);
+
+	_ = sqlx::query!(
+		"DELETE FROM pvs WHERE post_id = -1 AND import_id = ANY($1)",
+		&removed
+	)
+	.execute(&state.db)
+	.await;
+	_ = sqlx::query!(
+		"DELETE FROM modules WHERE post_id = -1 AND import_id = ANY($1)",
+		&removed
+	)
+	.execute(&state.db)
+	.await;
+	_ = sqlx::query!(
+		"DELETE FROM cstm_items WHERE post_id = -1 AND import_id = ANY($1)",
+		&removed
+	)
+	.execute(&state.db)
+	.await;
+	_ = sqlx::query!(
+		"DELETE FROM nc_songs WHERE post_id = -1 AND import_id = ANY($1)",
+		&removed
+	)
+	.execute(&state.db)
+	.await;
+	_ = sqlx::query!(
+		"DELETE FROM db_entries WHERE post_id = -1 AND import_id = ANY($1)",
+		&removed
+	)
+	.execute(&state.db)
+	.await;
+	delete_indexed_data(-1, state).await;
+
+	for (id, path) in &reapply {
+		apply_base_game_dump(path, *id, state).await;
+	}
+	sync_extracted_data(Some(-1), state).await;
+	optimise_all_reservations(state).await;
+
+	let mut post_ids = affected;
+	for (id, _) in &reapply {
+		post_ids.extend(base_game_import_posts(*id, state).await);
+	}
+	post_ids.sort();
+	post_ids.dedup();
+	reextract_posts(post_ids, 4, state.clone()).await;
+
+	Some(())
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/ids/base_game",
+	request_body = BaseGameImportArgs,
+	responses(
+		(status = 200),
+		(status = 400),
+		(status = 401),
+		(status = 409)
+	)
+)]
+pub async fn start_base_game_import(
+	user: User,
+	State(state): State<AppState>,
+	Json(args): Json<BaseGameImportArgs>,
+) -> StatusCode {
+	if !user.is_admin(&state.config) {
+		return StatusCode::UNAUTHORIZED;
+	}
+
+	if args.version.trim().is_empty() || !Path::new(&args.path).is_dir() {
+		return StatusCode::BAD_REQUEST;
+	}
+
+	if !claim_reextract(&state).await {
+		return StatusCode::CONFLICT;
+	}
+
+	tokio::spawn(async move {
+		import_base_game(&args, &state).await;
+		finish_reextract(&state).await;
+	});
+
+	StatusCode::OK
+}
+
+/// Removes the entries of the latest base game import and re-extracts the posts affected by it
+#[utoipa::path(
+	post,
+	path = "/api/v1/ids/base_game/{id}/rollback",
+	params(
+		("id" = i32, Path)
+	),
+	responses(
+		(status = 200),
+		(status = 401),
+		(status = 409)
+	)
+)]
+pub async fn rollback_base_game(
+	axum::extract::Path(id): axum::extract::Path<i32>,
+	user: User,
+	State(state): State<AppState>,
+) -> StatusCode {
+	if !user.is_admin(&state.config) {
+		return StatusCode::UNAUTHORIZED;
+	}
+
+	let latest = sqlx::query!(
+		"SELECT id FROM base_game_imports WHERE rolled_back = false ORDER BY id DESC LIMIT 1"
+	)
+	.fetch_optional(&state.db)
+	.await
+	.ok()
+	.flatten();
+	if latest.is_none_or(|latest| latest.id != id) || !claim_reextract(&state).await {
+		return StatusCode::CONFLICT;
+	}
+
+	tokio::spawn(async move {
+		rollback_base_game_import(id, &state).await;
+		finish_reextract(&state).await;
+	});
+
+	StatusCode::OK
+}
+
+// Marks a re-extract as running unless one already is, both under the same lock so only one caller can win
+async fn claim_reextract(state: &AppState) -> bool {
+	let mut progress = state.reextract_progress.lock().await;
+	if progress.as_ref().is_some_and(|progress| progress.running) {
+		return false;
+	}
+
+	*progress = Some(ReextractProgress {
+		total: 0,
+		completed: 0,
+		failed: Vec::new(),
+		running: true,
+		started: time::OffsetDateTime::now_utc(),
+	});
+	true
+}
+
+// Imports and rollbacks can stop before they get to re-extracting, so they always clear the flag themselves
+async fn finish_reextract(state: &AppState) {
+	if let Some(progress) = state.reextract_progress.lock().await.as_mut() {
+		progress.running = false;
+	}
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/ids/base_game",
+	responses(
+		(status = 200, body = Vec<BaseGameImport>, content_type = "application/json")
+	)
+)]
+pub async fn base_game_imports(
+	State(state): State<AppState>,
+) -> Result<Json<Vec<BaseGameImport>>, StatusCode> {
+	let imports = sqlx::query!(
+		"SELECT id, version, replaced, rolled_back, pvs, modules, cstm_items, db_entries, time FROM base_game_imports ORDER BY time DESC"
+	)
+	.fetch_all(&state.db)
+	.await
+	.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	Ok(Json(
+		imports
+			.into_iter()
+			.map(|import| BaseGameImport {
+				id: import.id,
+				version: import.version,
+				replaced: import.replaced,
+				rolled_back: import.rolled_back,
+				pvs: import.pvs,
+				modules: import.modules,
+				cstm_items: import.cstm_items,
+				db_entries: import.db_entries,
+				time: import.time.assume_offset(time::UtcOffset::UTC),
+			})
+			.collect(),
+	))
+}
+
+async fn extract_rom_dirs(
+	root: &str,
+	mod_folder: &str,
+	post_id: i32,
+	state: &AppState,
+	archive: &mut crate::api::lint::ArchiveContents,
+) {
+	// pv_db script and song paths are relative to the rom folders parent, such as rom/script/pv_001_easy.dsc
+	let mut rom_files = BTreeMap::new();
+	for rom in &ROM_DIRS {
+		let rom_root = format!("{root}/{rom}");
+		for file in walkdir::WalkDir::new(&rom_root)
+			.into_iter()
+			.filter_map(|file| file.ok())
+			.filter(|file| {
+				file.path()
+					.extension()
+					.is_some_and(|ext| ext == "dsc" || ext == "ogg" || ext == "wav")
+			}) {
+			let Ok(name) = file.path().strip_prefix(&rom_root) else {
+				continue;
+			};
+			let Some(name) = name.to_str() else {
+				continue;
+			};
+			rom_files
+				.entry(String::from(name))
+				.or_insert_with(|| file.path().to_path_buf());
+		}
+	}
+	archive.scripts.extend(
+		rom_files
+			.keys()
+			.filter(|name| name.ends_with(".dsc"))
+			.cloned(),
+	);
+
+	for rom in ROM_DIRS {
+		let folder = format!("{root}/{rom}/rom");
+		let path = Path::new(&folder);
+		if !path.exists() {
+			continue;
+		}
+
+		let nc_db = format!("{folder}/nc_db.toml");
+		let path = Path::new(&nc_db);
+		if path.exists() {
+			if let Ok(data) = tokio::fs::read_to_string(&path).await {
+				parse_nc_db(&data, post_id, state.clone()).await;
+			}
+		}
+
+		for prefix in DB_PREFIXES {
+			for file in ["pv_db.txt", "nc_pv_db.txt"] {
+				let pv_db = format!("{folder}/{prefix}{file}");
+				let Ok(data) = tokio::fs::read_to_string(&pv_db).await else {
+					continue;
+				};
+				let Some(pv_db) = pv_db::PvDb::from_str(&data) else {
+					continue;
+				};
+				parse_pv_db(&pv_db, &rom_files, post_id, state.clone()).await;
+				archive.pv_dbs.push(crate::api::lint::ArchiveFile {
+					folder: String::from(mod_folder),
+					rom,
+					prefix,
+					file,
+					data: pv_db,
+				});
+			}
+
+			let spr_db = format!("/{folder}/2d/{prefix}spr_db.bin");
+			if let Ok(spr_db) = diva_db::SprDb::from_file(&spr_db) {
+				let mut sets = BTreeMap::new();
+				for (id, set) in &spr_db.sets {
+					let location = crate::thumbnails::SpriteLocation {
+						farc: Path::new(&folder)
+							.join("2d")
+							.join(format!("{}.farc", set.name.to_lowercase())),
+						set: set.name.clone(),
+					};
+					for (_, sprite) in &set.sprites {
+						archive
+							.sprite_locations
+							.insert(sprite.name.clone(), location.clone());
+						archive.sprites.insert(sprite.name.clone());
+					}
+					sets.insert(*id, set.name.clone());
+				}
+				archive.db_entries.push(crate::api::lint::ArchiveFile {
+					folder: String::from(mod_folder),
+					rom,
+					prefix,
+					file: "spr_db.bin",
+					data: sets,
+				});
+				parse_spr_db(spr_db, post_id, &state).await;
+			}
 
-						let spr_db = format!("/{folder}/2d/{prefix}spr_db.bin");
-						let path = Path::new(&spr_db);
-						if path.exists() {
-							parse_spr_db(spr_db, post_id, &state).await;
-						}
+			let aet_db = format!("/{folder}/2d/{prefix}aet_db.bin");
+			if let Ok(aet_db) = diva_db::AetDb::from_file(&aet_db) {
+				archive.db_entries.push(crate::api::lint::ArchiveFile {
+					folder: String::from(mod_folder),
+					rom,
+					prefix,
+					file: "aet_db.bin",
+					data: aet_db
+						.sets
+						.iter()
+						.map(|(id, set)| (*id, set.name.clone()))
+						.collect(),
+				});
+				parse_aet_db(aet_db, post_id, &state).await;
+			}
 
-						let aet_db = format!("/{folder}/2d/{prefix}aet_db.bin");
-						let path = Path::new(&aet_db);
-						if path.exists() {
-							parse_aet_db(aet_db, post_id, &state).await;
-						}
+			let obj_db = format!("/{folder}/objset/{prefix}obj_db.bin");
+			if let Ok(obj_db) = diva_db::ObjDb::from_file(&obj_db) {
+				let mut sets = BTreeMap::new();
+				for (id, set) in &obj_db.sets {
+					archive.objsets.insert(set.name.clone());
+					sets.insert(*id, set.name.clone());
+				}
+				archive.db_entries.push(crate::api::lint::ArchiveFile {
+					folder: String::from(mod_folder),
+					rom,
+					prefix,
+					file: "obj_db.bin",
+					data: sets,
+				});
+				parse_obj_db(obj_db, post_id, &state).await;
+			}
 
-						let obj_db = format!("/{folder}/objset/{prefix}obj_db.bin");
-						let path = Path::new(&obj_db);
-						if path.exists() {
-							parse_obj_db(obj_db, post_id, &state).await;
-						}
+			let tex_db = format!("/{folder}/objset/{prefix}tex_db.bin");
+			if let Ok(tex_db) = diva_db::TexDb::from_file(&tex_db) {
+				archive.db_entries.push(crate::api::lint::ArchiveFile {
+					folder: String::from(mod_folder),
+					rom,
+					prefix,
+					file: "tex_db.bin",
+					data: tex_db
+						.textures
+						.iter()
+						.map(|(id, name)| (*id, name.clone()))
+						.collect(),
+				});
+				parse_tex_db(tex_db, post_id, &state).await;
+			}
 
-						let tex_db = format!("/{folder}/objset/{prefix}tex_db.bin");
-						let path = Path::new(&tex_db);
-						if path.exists() {
-							parse_tex_db(tex_db, post_id, &state).await;
-						}
+			let module_tbl = format!("{folder}/{prefix}gm_module_tbl.farc");
+			let module_tbl = Path::new(&module_tbl);
+			let customize_item_tbl = format!("{folder}/{prefix}gm_customize_item_tbl.farc");
+			let customize_item_tbl = Path::new(&customize_item_tbl);
+			if module_tbl.exists() || customize_item_tbl.exists() {
+				let chritm_prop = format!("{folder}/{prefix}chritm_prop.farc");
+				let chritm_prop = Path::new(&chritm_prop);
+				let str_array = format!("{folder}/lang2/mod_str_array.toml");
+				let str_array = Path::new(&str_array);
+
+				let module_tbl = if module_tbl.exists() {
+					Some(module_tbl)
+				} else {
+					None
+				};
+				let customize_item_tbl = if customize_item_tbl.exists() {
+					Some(customize_item_tbl)
+				} else {
+					None
+				};
+				let chritm_prop = if chritm_prop.exists() {
+					Some(chritm_prop)
+				} else {
+					None
+				};
+				let str_array = if str_array.exists() {
+					Some(str_array)
+				} else {
+					None
+				};
 
-						let module_tbl = format!("{folder}/{prefix}gm_module_tbl.farc");
-						let module_tbl = Path::new(&module_tbl);
-						let customize_item_tbl =
-							format!("{folder}/{prefix}gm_customize_item_tbl.farc");
-						let customize_item_tbl = Path::new(&customize_item_tbl);
-						if module_tbl.exists() || customize_item_tbl.exists() {
-							let chritm_prop = format!("{folder}/{prefix}chritm_prop.farc");
-							let chritm_prop = Path::new(&chritm_prop);
-							let str_array = format!("{folder}/lang2/mod_str_array.toml");
-							let str_array = Path::new(&str_array);
-
-							let module_tbl = if module_tbl.exists() {
-								Some(module_tbl)
-							} else {
-								None
-							};
-							let customize_item_tbl = if customize_item_tbl.exists() {
-								Some(customize_item_tbl)
-							} else {
-								None
-							};
-							let chritm_prop = if chritm_prop.exists() {
-								Some(chritm_prop)
-							} else {
-								None
-							};
-							let str_array = if str_array.exists() {
-								Some(str_array)
-							} else {
-								None
-							};
-
-							parse_module_db(
-								module_tbl,
-								customize_item_tbl,
-								chritm_prop,
-								str_array,
-								post_id,
-								state.clone(),
-							)
-							.await;
-						}
-					}
+				if let Some(module_db) = module_db::ModuleDb::from_files(
+					module_tbl,
+					customize_item_tbl,
+					chritm_prop,
+					str_array,
+				) {
+					parse_module_db(&module_db, post_id, state.clone()).await;
+					archive.module_dbs.push(crate::api::lint::ArchiveFile {
+						folder: String::from(mod_folder),
+						rom,
+						prefix,
+						file: "gm_module_tbl.farc",
+						data: module_db,
+					});
 				}
 			}
 		}
 	}
+}
 
+pub async fn optimise_all_reservations(state: &AppState) {
 	optimise_reservations(ReservationType::Song, state.clone()).await;
 	optimise_reservations(ReservationType::Module, state.clone()).await;
 	optimise_reservations(ReservationType::CstmItem, state.clone()).await;
@@ -386,13 +1133,9 @@ pub async fn extract_post_data(post_id: i32, state: AppState) -> Option<()> {
 	optimise_reservations(ReservationType::CosMeiko, state.clone()).await;
 	optimise_reservations(ReservationType::CosSakine, state.clone()).await;
 	optimise_reservations(ReservationType::CosTeto, state.clone()).await;
-
-	Some(())
 }
 
-async fn parse_spr_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -> Option<()> {
-	let spr_db = diva_db::SprDb::from_file(path).ok()?;
-
+async fn parse_spr_db(spr_db: diva_db::SprDb, post_id: i32, state: &AppState) -> Option<()> {
 	let mut sets = Vec::new();
 	let mut sprites = Vec::new();
 	for (id, set) in spr_db.sets {
@@ -422,24 +1165,23 @@ async fn parse_spr_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -
 		}
 	}
 
-	let base =
-		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("sprite_sets"))
-			.with_filter("post_id=-1")
-			.with_limit(u32::MAX as usize)
-			.execute::<MeilisearchDbEntry>()
-			.await
-			.ok()?;
+	let base = if post_id == -1 {
+		Vec::new()
+	} else {
+		load_db_entries("sprite_sets", Some(-1), state).await?
+	};
 
 	let sets = sets
 		.into_iter()
 		.filter(|entry| {
 			!base
-				.results
 				.iter()
 				.any(|base| base.id == entry.id && base.name == entry.name)
 		})
 		.collect::<Vec<_>>();
 
+	store_db_entries("sprite_sets", &sets, state).await?;
+
 	state
 		.meilisearch
 		.index("sprite_sets")
@@ -447,23 +1189,23 @@ async fn parse_spr_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -
 		.await
 		.ok()?;
 
-	let base = meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("sprites"))
-		.with_filter("post_id=-1")
-		.with_limit(u32::MAX as usize)
-		.execute::<MeilisearchDbEntry>()
-		.await
-		.ok()?;
+	let base = if post_id == -1 {
+		Vec::new()
+	} else {
+		load_db_entries("sprites", Some(-1), state).await?
+	};
 
 	let sprites = sprites
 		.into_iter()
 		.filter(|entry| {
 			!base
-				.results
 				.iter()
 				.any(|base| base.id == entry.id && base.name == entry.name)
 		})
 		.collect::<Vec<_>>();
 
+	store_db_entries("sprites", &sprites, state).await?;
+
 	state
 		.meilisearch
 		.index("sprites")
@@ -474,9 +1216,7 @@ async fn parse_spr_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -
 	Some(())
 }
 
-async fn parse_aet_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -> Option<()> {
-	let aet_db = diva_db::AetDb::from_file(path).ok()?;
-
+async fn parse_aet_db(aet_db: diva_db::AetDb, post_id: i32, state: &AppState) -> Option<()> {
 	let mut set_entries = Vec::new();
 	let mut scene_entries = Vec::new();
 	for (id, set) in aet_db.sets {
@@ -497,24 +1237,23 @@ async fn parse_aet_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -
 		}
 	}
 
-	let base_sets =
-		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("aet_sets"))
-			.with_filter("post_id=-1")
-			.with_limit(u32::MAX as usize)
-			.execute::<MeilisearchDbEntry>()
-			.await
-			.ok()?;
+	let base_sets = if post_id == -1 {
+		Vec::new()
+	} else {
+		load_db_entries("aet_sets", Some(-1), state).await?
+	};
 
 	let set_entries = set_entries
 		.into_iter()
 		.filter(|entry| {
 			!base_sets
-				.results
 				.iter()
 				.any(|base| base.id == entry.id && base.name == entry.name)
 		})
 		.collect::<Vec<_>>();
 
+	store_db_entries("aet_sets", &set_entries, state).await?;
+
 	state
 		.meilisearch
 		.index("aet_sets")
@@ -522,24 +1261,23 @@ async fn parse_aet_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -
 		.await
 		.ok()?;
 
-	let base_scenes =
-		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("aet_scenes"))
-			.with_filter("post_id=-1")
-			.with_limit(u32::MAX as usize)
-			.execute::<MeilisearchDbEntry>()
-			.await
-			.ok()?;
+	let base_scenes = if post_id == -1 {
+		Vec::new()
+	} else {
+		load_db_entries("aet_scenes", Some(-1), state).await?
+	};
 
 	let scene_entries = scene_entries
 		.into_iter()
 		.filter(|entry| {
 			!base_scenes
-				.results
 				.iter()
 				.any(|base| base.id == entry.id && base.name == entry.name)
 		})
 		.collect::<Vec<_>>();
 
+	store_db_entries("aet_scenes", &scene_entries, state).await?;
+
 	state
 		.meilisearch
 		.index("aet_scenes")
@@ -550,9 +1288,7 @@ async fn parse_aet_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -
 	Some(())
 }
 
-async fn parse_obj_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -> Option<()> {
-	let obj_db = diva_db::ObjDb::from_file(path).ok()?;
-
+async fn parse_obj_db(obj_db: diva_db::ObjDb, post_id: i32, state: &AppState) -> Option<()> {
 	let mut entries = Vec::new();
 	for (id, set) in obj_db.sets {
 		entries.push(MeilisearchDbEntry {
@@ -563,23 +1299,23 @@ async fn parse_obj_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -
 		});
 	}
 
-	let base = meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("objsets"))
-		.with_filter("post_id=-1")
-		.with_limit(u32::MAX as usize)
-		.execute::<MeilisearchDbEntry>()
-		.await
-		.ok()?;
+	let base = if post_id == -1 {
+		Vec::new()
+	} else {
+		load_db_entries("objsets", Some(-1), state).await?
+	};
 
 	let entries = entries
 		.into_iter()
 		.filter(|entry| {
 			!base
-				.results
 				.iter()
 				.any(|base| base.id == entry.id && base.name == entry.name)
 		})
 		.collect::<Vec<_>>();
 
+	store_db_entries("objsets", &entries, state).await?;
+
 	state
 		.meilisearch
 		.index("objsets")
@@ -590,9 +1326,7 @@ async fn parse_obj_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -
 	Some(())
 }
 
-async fn parse_tex_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -> Option<()> {
-	let tex_db = diva_db::TexDb::from_file(path).ok()?;
-
+async fn parse_tex_db(tex_db: diva_db::TexDb, post_id: i32, state: &AppState) -> Option<()> {
 	let mut entries = Vec::new();
 	for (id, name) in tex_db.textures {
 		entries.push(MeilisearchDbEntry {
@@ -603,24 +1337,23 @@ async fn parse_tex_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -
 		});
 	}
 
-	let base =
-		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("textures"))
-			.with_filter("post_id=-1")
-			.with_limit(u32::MAX as usize)
-			.execute::<MeilisearchDbEntry>()
-			.await
-			.ok()?;
+	let base = if post_id == -1 {
+		Vec::new()
+	} else {
+		load_db_entries("textures", Some(-1), state).await?
+	};
 
 	let entries = entries
 		.into_iter()
 		.filter(|entry| {
 			!base
-				.results
 				.iter()
 				.any(|base| base.id == entry.id && base.name == entry.name)
 		})
 		.collect::<Vec<_>>();
 
+	store_db_entries("textures", &entries, state).await?;
+
 	state
 		.meilisearch
 		.index("textures")
@@ -631,50 +1364,43 @@ async fn parse_tex_db<P: AsRef<Path>>(path: P, post_id: i32, state: &AppState) -
 	Some(())
 }
 
-async fn parse_module_db<P: AsRef<Path>>(
-	module_tbl: Option<P>,
-	customize_item_tbl: Option<P>,
-	chritm_prop: Option<P>,
-	str_array: Option<P>,
+async fn parse_module_db(
+	module_db: &module_db::ModuleDb,
 	post_id: i32,
 	state: AppState,
 ) -> Option<()> {
-	let module_db =
-		module_db::ModuleDb::from_files(module_tbl, customize_item_tbl, chritm_prop, str_array)?;
-
 	let modules = module_db
 		.modules
-		.into_iter()
+		.iter()
 		.map(|(id, module)| MeilisearchModule {
-			uid: (post_id as u64) << 32 | (id as u64),
+			uid: (post_id as u64) << 32 | (*id as u64),
 			post_id,
-			module_id: id,
-			module: module,
+			module_id: *id,
+			module: module.clone(),
 		})
 		.collect::<Vec<_>>();
 
 	let cstm_items = module_db
 		.cstm_items
-		.into_iter()
+		.iter()
 		.map(|(id, cstm_item)| MeilisearchCstmItem {
-			uid: (post_id as u64) << 32 | (id as u64),
+			uid: (post_id as u64) << 32 | (*id as u64),
 			post_id,
-			customize_item_id: id,
-			customize_item: cstm_item,
+			customize_item_id: *id,
+			customize_item: cstm_item.clone(),
 		})
 		.collect::<Vec<_>>();
 
-	let base = meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("modules"))
-		.with_filter("post_id=-1")
-		.with_limit(u32::MAX as usize)
-		.execute::<MeilisearchModule>()
-		.await
-		.ok()?;
+	let base = if post_id == -1 {
+		Vec::new()
+	} else {
+		load_modules(Some(-1), &state).await?
+	};
 
 	let mut modules = modules
 		.into_iter()
 		.filter(|module| {
-			!base.results.iter().any(|base| {
+			!base.iter().any(|base| {
 				base.module_id == module.module_id && base.module.name_jp == module.module.name_jp
 			})
 		})
@@ -688,6 +1414,8 @@ async fn parse_module_db<P: AsRef<Path>>(
 			.retain(|item| !item.objset.is_empty());
 	}
 
+	store_modules(&modules, &state).await?;
+
 	state
 		.meilisearch
 		.index("modules")
@@ -695,24 +1423,24 @@ async fn parse_module_db<P: AsRef<Path>>(
 		.await
 		.ok()?;
 
-	let base =
-		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("cstm_items"))
-			.with_filter("post_id=-1")
-			.with_limit(u32::MAX as usize)
-			.execute::<MeilisearchCstmItem>()
-			.await
-			.ok()?;
+	let base = if post_id == -1 {
+		Vec::new()
+	} else {
+		load_cstm_items(Some(-1), &state).await?
+	};
 
 	let cstm_items = cstm_items
 		.into_iter()
 		.filter(|cstm_item| {
-			!base.results.iter().any(|base| {
+			!base.iter().any(|base| {
 				base.customize_item_id == cstm_item.customize_item_id
 					&& base.customize_item.name_jp == cstm_item.customize_item.name_jp
 			})
 		})
 		.collect::<Vec<_>>();
 
+	store_cstm_items(&cstm_items, &state).await?;
+
 	state
 		.meilisearch
 		.index("cstm_items")
@@ -723,41 +1451,87 @@ async fn parse_module_db<P: AsRef<Path>>(
 	Some(())
 }
 
-async fn parse_pv_db(data: &str, post_id: i32, state: AppState) -> Option<()> {
-	let pv_db = pv_db::PvDb::from_str(data)?;
+fn level_stars(level: &pv_db::Level) -> Option<f32> {
+	let level = serde_json::to_string(level).ok()?;
+	let (whole, half) = level
+		.trim_matches('"')
+		.strip_prefix("PV_LV_")?
+		.split_once('_')?;
+	Some(whole.parse::<f32>().ok()? + half.parse::<f32>().ok()? / 10.0)
+}
+
+async fn read_chart(
+	script_file_name: Option<&String>,
+	rom_files: &BTreeMap<String, std::path::PathBuf>,
+) -> Option<crate::dsc::Chart> {
+	let script_file_name = script_file_name?;
+	let Some(path) = rom_files.get(script_file_name) else {
+		return Some(crate::dsc::Chart::missing(script_file_name));
+	};
+	let data = tokio::fs::read(path).await.ok()?;
+	crate::dsc::Chart::parse(script_file_name, &data)
+}
 
+async fn parse_pv_db(
+	pv_db: &pv_db::PvDb,
+	rom_files: &BTreeMap<String, std::path::PathBuf>,
+	post_id: i32,
+	state: AppState,
+) -> Option<()> {
 	let mut documents = Vec::new();
 	for (id, entry) in pv_db.pvs.iter() {
 		let mut levels = [const { None }; 5];
+		let mut charts = [const { None }; 5];
+		let mut metadata = PvMetadata {
+			bpm: entry.bpm.map(|bpm| bpm as i32),
+			performers: entry
+				.performer
+				.iter()
+				.flatten()
+				.filter_map(|performer| performer.chara.as_ref())
+				.map(|chara| chara.to_string().to_uppercase())
+				.sorted()
+				.dedup()
+				.collect(),
+			sabi: entry.sabi.as_ref().and_then(|sabi| {
+				Some(PvSabi {
+					start_time: sabi.start_time? as f32,
+					play_time: sabi.play_time? as f32,
+				})
+			}),
+			lyrics: entry.lyric.as_ref().is_some_and(|lyric| !lyric.is_empty()),
+			lyrics_en: entry
+				.lyric_en
+				.as_ref()
+				.is_some_and(|lyric| !lyric.is_empty()),
+			..Default::default()
+		};
 		if let Some(difficulties) = &entry.difficulty {
-			if let Some(easys) = &difficulties.easy {
-				for easy in easys {
-					if easy.edition == Some(0) {
-						levels[0] = easy.level.clone();
-					}
-				}
-			}
-			if let Some(normals) = &difficulties.normal {
-				for normal in normals {
-					if normal.edition == Some(0) {
-						levels[1] = normal.level.clone();
-					}
-				}
-			}
-			if let Some(hards) = &difficulties.hard {
-				for hard in hards {
-					if hard.edition == Some(0) {
-						levels[2] = hard.level.clone();
-					}
-				}
-			}
-			if let Some(extremes) = &difficulties.extreme {
-				for extreme in extremes {
-					if extreme.edition == Some(0) {
-						levels[3] = extreme.level.clone();
-					} else if extreme.edition == Some(1) {
-						levels[4] = extreme.level.clone();
-					}
+			let groups = [
+				&difficulties.easy,
+				&difficulties.normal,
+				&difficulties.hard,
+				&difficulties.extreme,
+			];
+			for (i, group) in groups.into_iter().enumerate() {
+				for difficulty in group.iter().flatten() {
+					// Extra extreme charts are stored as the second edition of extreme
+					let slot = match (i, difficulty.edition) {
+						(_, Some(0)) => i,
+						(3, Some(1)) => 4,
+						_ => continue,
+					};
+					levels[slot] = difficulty.level.clone();
+					charts[slot] =
+						read_chart(difficulty.script_file_name.as_ref(), rom_files).await;
+					metadata.stars[slot] = difficulty.level.as_ref().and_then(level_stars);
+					let attribute = difficulty.attribute.as_ref();
+					metadata.difficulties[slot] = Some(DifficultyAttributes {
+						edition: if slot == 4 { 1 } else { 0 },
+						original: attribute.and_then(|attribute| attribute.original) == Some(1),
+						extra: attribute.and_then(|attribute| attribute.extra) == Some(1),
+						slide: attribute.and_then(|attribute| attribute.slide) == Some(1),
+					});
 				}
 			}
 		}
@@ -770,20 +1544,23 @@ async fn parse_pv_db(data: &str, post_id: i32, state: AppState) -> Option<()> {
 			song_info: entry.songinfo.clone(),
 			song_info_en: entry.songinfo_en.clone(),
 			levels,
+			charts,
+			metadata,
+			preview: false,
+			song_keys: crate::api::songs::song_keys(&entry.song_name, &entry.song_name_en),
 		});
 	}
 
-	let base = meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("pvs"))
-		.with_filter("post=-1")
-		.with_limit(u32::MAX as usize)
-		.execute::<MeilisearchPv>()
-		.await
-		.unwrap();
+	let base = if post_id == -1 {
+		Vec::new()
+	} else {
+		load_pvs(Some(-1), &state).await?
+	};
 
-	let pvs = documents
+	let mut pvs = documents
 		.into_iter()
 		.filter(|pv| {
-			!base.results.iter().any(|base| {
+			!base.iter().any(|base| {
 				base.pv_id == pv.pv_id
 					&& base.song_name == pv.song_name
 					&& base.song_name_en == pv.song_name_en
@@ -791,6 +1568,27 @@ async fn parse_pv_db(data: &str, post_id: i32, state: AppState) -> Option<()> {
 		})
 		.collect::<Vec<_>>();
 
+	for pv in &mut pvs {
+		let Some((_, entry)) = pv_db.pvs.iter().find(|(id, _)| **id as i32 == pv.pv_id) else {
+			continue;
+		};
+		let Some(song) = entry
+			.song_file_name
+			.as_ref()
+			.and_then(|song_file_name| rom_files.get(song_file_name))
+		else {
+			continue;
+		};
+		let start = pv
+			.metadata
+			.sabi
+			.as_ref()
+			.map_or(0.0, |sabi| sabi.start_time);
+		pv.preview = crate::previews::render_preview(post_id, pv.pv_id, song, start, &state).await;
+	}
+
+	store_pvs(&pvs, &state).await?;
+
 	state
 		.meilisearch
 		.index("pvs")
@@ -955,16 +1753,474 @@ async fn parse_nc_db(data: &str, post_id: i32, state: AppState) -> Option<()> {
 		})
 		.collect::<Vec<_>>();
 
-	state
-		.meilisearch
-		.index("nc_songs")
-		.add_or_update(&songs, Some("uid"))
+	store_nc_songs(&songs, &state).await?;
+
+	state
+		.meilisearch
+		.index("nc_songs")
+		.add_or_update(&songs, Some("uid"))
+		.await
+		.unwrap();
+
+	Some(())
+}
+
+pub const DB_ENTRY_INDEXES: [&str; 6] = [
+	"sprite_sets",
+	"sprites",
+	"aet_sets",
+	"aet_scenes",
+	"objsets",
+	"textures",
+];
+
+pub async fn store_pvs(pvs: &[MeilisearchPv], state: &AppState) -> Option<()> {
+	let mut transaction = state.db.begin().await.ok()?;
+	for pv in pvs {
+		sqlx::query!(
+			"INSERT INTO pvs (post_id, pv_id, song_name, song_name_en, song_info, song_info_en, levels, charts, metadata, preview) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) ON CONFLICT (post_id, pv_id) DO UPDATE SET song_name = EXCLUDED.song_name, song_name_en = EXCLUDED.song_name_en, song_info = EXCLUDED.song_info, song_info_en = EXCLUDED.song_info_en, levels = EXCLUDED.levels, charts = EXCLUDED.charts, metadata = EXCLUDED.metadata, preview = EXCLUDED.preview, import_id = NULL",
+			pv.post,
+			pv.pv_id,
+			pv.song_name,
+			pv.song_name_en,
+			pv.song_info.as_ref().map(sqlx::types::Json) as _,
+			pv.song_info_en.as_ref().map(sqlx::types::Json) as _,
+			sqlx::types::Json(&pv.levels) as _,
+			sqlx::types::Json(&pv.charts) as _,
+			sqlx::types::Json(&pv.metadata) as _,
+			pv.preview,
+		)
+		.execute(&mut *transaction)
+		.await
+		.ok()?;
+	}
+	transaction.commit().await.ok()
+}
+
+pub async fn store_modules(modules: &[MeilisearchModule], state: &AppState) -> Option<()> {
+	let mut transaction = state.db.begin().await.ok()?;
+	for module in modules {
+		let chara = serde_json::to_string(&module.module.chara).ok()?;
+		sqlx::query!(
+			"INSERT INTO modules (post_id, module_id, chara, cos_id, module) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (post_id, module_id) DO UPDATE SET chara = EXCLUDED.chara, cos_id = EXCLUDED.cos_id, module = EXCLUDED.module, import_id = NULL",
+			module.post_id,
+			module.module_id,
+			chara.trim_matches('\"'),
+			module.module.cos.id,
+			sqlx::types::Json(&module.module) as _,
+		)
+		.execute(&mut *transaction)
+		.await
+		.ok()?;
+	}
+	transaction.commit().await.ok()
+}
+
+pub async fn store_cstm_items(cstm_items: &[MeilisearchCstmItem], state: &AppState) -> Option<()> {
+	let mut transaction = state.db.begin().await.ok()?;
+	for cstm_item in cstm_items {
+		sqlx::query!(
+			"INSERT INTO cstm_items (post_id, customize_item_id, customize_item) VALUES ($1, $2, $3) ON CONFLICT (post_id, customize_item_id) DO UPDATE SET customize_item = EXCLUDED.customize_item, import_id = NULL",
+			cstm_item.post_id,
+			cstm_item.customize_item_id,
+			sqlx::types::Json(&cstm_item.customize_item) as _,
+		)
+		.execute(&mut *transaction)
+		.await
+		.ok()?;
+	}
+	transaction.commit().await.ok()
+}
+
+pub async fn store_nc_songs(nc_songs: &[MeilisearchNcSong], state: &AppState) -> Option<()> {
+	let mut transaction = state.db.begin().await.ok()?;
+	for nc_song in nc_songs {
+		sqlx::query!(
+			"INSERT INTO nc_songs (post_id, pv_id, difficulties) VALUES ($1, $2, $3) ON CONFLICT (post_id, pv_id) DO UPDATE SET difficulties = EXCLUDED.difficulties, import_id = NULL",
+			nc_song.post_id,
+			nc_song.pv_id,
+			sqlx::types::Json(&nc_song.difficulties) as _,
+		)
+		.execute(&mut *transaction)
+		.await
+		.ok()?;
+	}
+	transaction.commit().await.ok()
+}
+
+pub async fn store_db_entries(
+	index: &str,
+	entries: &[MeilisearchDbEntry],
+	state: &AppState,
+) -> Option<()> {
+	// Postgres refuses to upsert the same row twice in one statement, later entries win like they do in meilisearch
+	let entries = entries
+		.iter()
+		.map(|entry| ((entry.post_id, entry.id as i64), entry.name.as_str()))
+		.collect::<BTreeMap<_, _>>();
+
+	let mut post_ids = Vec::with_capacity(entries.len());
+	let mut ids = Vec::with_capacity(entries.len());
+	let mut names = Vec::with_capacity(entries.len());
+	for ((post_id, id), name) in entries {
+		post_ids.push(post_id);
+		ids.push(id);
+		names.push(name);
+	}
+
+	for ((post_ids, ids), names) in post_ids
+		.chunks(10000)
+		.zip(ids.chunks(10000))
+		.zip(names.chunks(10000))
+	{
+		sqlx::query!(
+			"INSERT INTO db_entries (db_type, post_id, id, name) SELECT $1, * FROM UNNEST($2::int[], $3::bigint[], $4::text[]) ON CONFLICT (db_type, post_id, id) DO UPDATE SET name = EXCLUDED.name, import_id = NULL",
+			index,
+			post_ids,
+			ids,
+			names,
+		)
+		.execute(&state.db)
+		.await
+		.ok()?;
+	}
+
+	Some(())
+}
+
+pub async fn load_pvs(post_id: Option<i32>, state: &AppState) -> Option<Vec<MeilisearchPv>> {
+	let pvs = sqlx::query!(
+		r#"
+		SELECT post_id, pv_id, song_name, song_name_en,
+			song_info as "song_info: sqlx::types::Json<pv_db::SongInfo>",
+			song_info_en as "song_info_en: sqlx::types::Json<pv_db::SongInfo>",
+			levels as "levels: sqlx::types::Json<[Option<pv_db::Level>; 5]>",
+			charts as "charts: sqlx::types::Json<[Option<crate::dsc::Chart>; 5]>",
+			metadata as "metadata: sqlx::types::Json<PvMetadata>",
+			preview
+		FROM pvs
+		WHERE $1::int IS NULL OR post_id = $1
+		"#,
+		post_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.ok()?;
+
+	Some(
+		pvs.into_iter()
+			.map(|pv| MeilisearchPv {
+				uid: (pv.post_id as u64) << 32 | (pv.pv_id as u64),
+				post: pv.post_id,
+				pv_id: pv.pv_id,
+				song_name: pv.song_name,
+				song_name_en: pv.song_name_en,
+				song_info: pv.song_info.map(|song_info| song_info.0),
+				song_info_en: pv.song_info_en.map(|song_info| song_info.0),
+				levels: pv.levels.0,
+				charts: pv.charts.0,
+				metadata: pv.metadata.0,
+				preview: pv.preview,
+				song_keys: crate::api::songs::song_keys(&pv.song_name, &pv.song_name_en),
+			})
+			.collect(),
+	)
+}
+
+pub async fn load_modules(
+	post_id: Option<i32>,
+	state: &AppState,
+) -> Option<Vec<MeilisearchModule>> {
+	let modules = sqlx::query!(
+		r#"
+		SELECT post_id, module_id, module as "module: sqlx::types::Json<module_db::Module>"
+		FROM modules
+		WHERE $1::int IS NULL OR post_id = $1
+		"#,
+		post_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.ok()?;
+
+	Some(
+		modules
+			.into_iter()
+			.map(|module| MeilisearchModule {
+				uid: (module.post_id as u64) << 32 | (module.module_id as u64),
+				post_id: module.post_id,
+				module_id: module.module_id,
+				module: module.module.0,
+			})
+			.collect(),
+	)
+}
+
+pub async fn load_cstm_items(
+	post_id: Option<i32>,
+	state: &AppState,
+) -> Option<Vec<MeilisearchCstmItem>> {
+	let cstm_items = sqlx::query!(
+		r#"
+		SELECT post_id, customize_item_id, customize_item as "customize_item: sqlx::types::Json<module_db::CustomizeItem>"
+		FROM cstm_items
+		WHERE $1::int IS NULL OR post_id = $1
+		"#,
+		post_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.ok()?;
+
+	Some(
+		cstm_items
+			.into_iter()
+			.map(|cstm_item| MeilisearchCstmItem {
+				uid: (cstm_item.post_id as u64) << 32 | (cstm_item.customize_item_id as u64),
+				post_id: cstm_item.post_id,
+				customize_item_id: cstm_item.customize_item_id,
+				customize_item: cstm_item.customize_item.0,
+			})
+			.collect(),
+	)
+}
+
+pub async fn load_nc_songs(
+	post_id: Option<i32>,
+	state: &AppState,
+) -> Option<Vec<MeilisearchNcSong>> {
+	let nc_songs = sqlx::query!(
+		r#"
+		SELECT post_id, pv_id, difficulties as "difficulties: sqlx::types::Json<[Option<MeilisearchNcDifficulty>; 5]>"
+		FROM nc_songs
+		WHERE $1::int IS NULL OR post_id = $1
+		"#,
+		post_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.ok()?;
+
+	Some(
+		nc_songs
+			.into_iter()
+			.map(|nc_song| MeilisearchNcSong {
+				uid: (nc_song.post_id as u64) << 32 | (nc_song.pv_id as u64),
+				post_id: nc_song.post_id,
+				pv_id: nc_song.pv_id,
+				difficulties: nc_song.difficulties.0,
+			})
+			.collect(),
+	)
+}
+
+pub async fn load_db_entries(
+	index: &str,
+	post_id: Option<i32>,
+	state: &AppState,
+) -> Option<Vec<MeilisearchDbEntry>> {
+	let entries = sqlx::query!(
+		"SELECT post_id, id, name FROM db_entries WHERE db_type = $1 AND ($2::int IS NULL OR post_id = $2)",
+		index,
+		post_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.ok()?;
+
+	Some(
+		entries
+			.into_iter()
+			.map(|entry| MeilisearchDbEntry {
+				uid: (entry.post_id as u64) << 32 | (entry.id as u64),
+				post_id: entry.post_id,
+				id: entry.id as u32,
+				name: entry.name,
+			})
+			.collect(),
+	)
+}
+
+pub async fn delete_extracted_data(post_id: i32, state: &AppState) {
+	_ = sqlx::query!("DELETE FROM pvs WHERE post_id = $1", post_id)
+		.execute(&state.db)
+		.await;
+	_ = sqlx::query!("DELETE FROM modules WHERE post_id = $1", post_id)
+		.execute(&state.db)
+		.await;
+	_ = sqlx::query!("DELETE FROM cstm_items WHERE post_id = $1", post_id)
+		.execute(&state.db)
+		.await;
+	_ = sqlx::query!("DELETE FROM nc_songs WHERE post_id = $1", post_id)
+		.execute(&state.db)
+		.await;
+	_ = sqlx::query!("DELETE FROM db_entries WHERE post_id = $1", post_id)
+		.execute(&state.db)
+		.await;
+	crate::thumbnails::delete_thumbnails(post_id, state).await;
+	crate::previews::delete_previews(post_id, state).await;
+	delete_indexed_data(post_id, state).await;
+}
+
+// Removes the extracted data of a post from meilisearch only, postgres is left as is
+async fn delete_indexed_data(post_id: i32, state: &AppState) {
+	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("pvs"))
+		.with_filter(&format!("post={post_id}"))
+		.execute::<MeilisearchPv>()
+		.await;
+
+	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("modules"))
+		.with_filter(&format!("post_id={post_id}"))
+		.execute::<MeilisearchModule>()
+		.await;
+
+	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
+		&state.meilisearch.index("cstm_items"),
+	)
+	.with_filter(&format!("post_id={post_id}"))
+	.execute::<MeilisearchCstmItem>()
+	.await;
+
+	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
+		&state.meilisearch.index("nc_songs"),
+	)
+	.with_filter(&format!("post_id={post_id}"))
+	.execute::<MeilisearchNcSong>()
+	.await;
+
+	for index in DB_ENTRY_INDEXES {
+		_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index(index))
+			.with_filter(&format!("post_id={post_id}"))
+			.execute::<MeilisearchDbEntry>()
+			.await;
+	}
+}
+
+// Rebuilds the meilisearch indexes for extracted data from postgres, either for one post or everything
+pub async fn sync_extracted_data(post_id: Option<i32>, state: &AppState) -> Option<()> {
+	for pvs in load_pvs(post_id, state).await?.chunks(10000) {
+		state
+			.meilisearch
+			.index("pvs")
+			.add_or_update(pvs, Some("uid"))
+			.await
+			.ok()?;
+	}
+
+	for modules in load_modules(post_id, state).await?.chunks(10000) {
+		state
+			.meilisearch
+			.index("modules")
+			.add_or_update(modules, Some("uid"))
+			.await
+			.ok()?;
+	}
+
+	for cstm_items in load_cstm_items(post_id, state).await?.chunks(10000) {
+		state
+			.meilisearch
+			.index("cstm_items")
+			.add_or_update(cstm_items, Some("uid"))
+			.await
+			.ok()?;
+	}
+
+	for nc_songs in load_nc_songs(post_id, state).await?.chunks(10000) {
+		state
+			.meilisearch
+			.index("nc_songs")
+			.add_or_update(nc_songs, Some("uid"))
+			.await
+			.ok()?;
+	}
+
+	for index in DB_ENTRY_INDEXES {
+		for entries in load_db_entries(index, post_id, state).await?.chunks(10000) {
+			state
+				.meilisearch
+				.index(index)
+				.add_or_update(entries, Some("uid"))
+				.await
+				.ok()?;
+		}
+	}
+
+	Some(())
+}
+
+// Copies everything already in meilisearch into postgres, used once when the tables are first created
+async fn import_extracted_data(state: &AppState) -> Option<()> {
+	let pvs = meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("pvs"))
+		.with_limit(u32::MAX as usize)
+		.execute::<MeilisearchPv>()
 		.await
-		.unwrap();
+		.ok()?;
+	store_pvs(&pvs.results, state).await?;
+
+	let modules =
+		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("modules"))
+			.with_limit(u32::MAX as usize)
+			.execute::<MeilisearchModule>()
+			.await
+			.ok()?;
+	store_modules(&modules.results, state).await?;
+
+	let cstm_items =
+		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("cstm_items"))
+			.with_limit(u32::MAX as usize)
+			.execute::<MeilisearchCstmItem>()
+			.await
+			.ok()?;
+	store_cstm_items(&cstm_items.results, state).await?;
+
+	let nc_songs =
+		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("nc_songs"))
+			.with_limit(u32::MAX as usize)
+			.execute::<MeilisearchNcSong>()
+			.await
+			.ok()?;
+	store_nc_songs(&nc_songs.results, state).await?;
+
+	for index in DB_ENTRY_INDEXES {
+		let entries =
+			meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index(index))
+				.with_limit(u32::MAX as usize)
+				.execute::<MeilisearchDbEntry>()
+				.await
+				.ok()?;
+		store_db_entries(index, &entries.results, state).await?;
+	}
 
 	Some(())
 }
 
+pub async fn restore_extracted_data(state: AppState) {
+	let Ok(stored) = sqlx::query!(
+		"SELECT (SELECT COUNT(*) FROM pvs) + (SELECT COUNT(*) FROM modules) + (SELECT COUNT(*) FROM db_entries) AS count"
+	)
+	.fetch_one(&state.db)
+	.await
+	else {
+		return;
+	};
+
+	if stored.count.unwrap_or(0) == 0 {
+		import_extracted_data(&state).await;
+		return;
+	}
+
+	let indexed = state
+		.meilisearch
+		.index("pvs")
+		.get_stats()
+		.await
+		.map_or(0, |stats| stats.number_of_documents);
+
+	if indexed == 0 {
+		sync_extracted_data(None, &state).await;
+	}
+}
+
 #[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct Pv {
 	pub uid: String,
@@ -975,9 +2231,49 @@ pub struct Pv {
 	pub song_info: Option<pv_db::SongInfo>,
 	pub song_info_en: Option<pv_db::SongInfo>,
 	pub levels: [Option<pv_db::Level>; 5],
+	pub charts: [Option<crate::dsc::Chart>; 5],
+	pub metadata: PvMetadata,
+	pub preview_url: Option<String>,
 }
 
 impl Pv {
+	pub fn thumbnail_url(&self) -> String {
+		format!("/thumbnails/pv/{}/{}.png", self.post.unwrap_or(-1), self.id)
+	}
+
+	pub fn has_charts(&self) -> bool {
+		self.charts.iter().any(|chart| chart.is_some())
+	}
+
+	/// Credit name, english and japanese value for every credit set in either song_info
+	pub fn credits(&self) -> Vec<(&'static str, String, String)> {
+		let fields: [(&'static str, fn(&pv_db::SongInfo) -> Option<&String>); 6] = [
+			("Music", |song_info| song_info.music.as_ref()),
+			("Lyrics", |song_info| song_info.lyrics.as_ref()),
+			("Arranger", |song_info| song_info.arranger.as_ref()),
+			("Manipulator", |song_info| song_info.manipulator.as_ref()),
+			("Editor", |song_info| song_info.pv_editor.as_ref()),
+			("Guitar", |song_info| song_info.guitar_player.as_ref()),
+		];
+
+		fields
+			.into_iter()
+			.filter_map(|(name, field)| {
+				let en = self.song_info_en.as_ref().and_then(field).cloned();
+				let jp = self.song_info.as_ref().and_then(field).cloned();
+				if en
+					.iter()
+					.chain(jp.iter())
+					.all(|credit| credit.trim().is_empty())
+				{
+					None
+				} else {
+					Some((name, en.unwrap_or_default(), jp.unwrap_or_default()))
+				}
+			})
+			.collect()
+	}
+
 	pub fn has_music(&self) -> bool {
 		if let Some(song_info) = &self.song_info {
 			if let Some(music) = &song_info.music {
@@ -1128,6 +2424,52 @@ pub struct Module {
 	pub module: module_db::Module,
 }
 
+impl Module {
+	pub fn thumbnail_url(&self) -> String {
+		format!(
+			"/thumbnails/module/{}/{}.png",
+			self.post.unwrap_or(-1),
+			self.id
+		)
+	}
+
+	pub fn url(&self) -> String {
+		format!("/module/{}/{}", self.post.unwrap_or(-1), self.id)
+	}
+
+	pub fn display_name(&self) -> String {
+		self.module
+			.name_en
+			.clone()
+			.or_else(|| self.module.name.clone())
+			.unwrap_or_default()
+	}
+
+	pub fn localized_names(&self) -> Vec<(&'static str, &String)> {
+		[
+			("JP", &self.module.name_jp),
+			("EN", &self.module.name_en),
+			("CN", &self.module.name_cn),
+			("FR", &self.module.name_fr),
+			("GE", &self.module.name_ge),
+			("IT", &self.module.name_it),
+			("KR", &self.module.name_kr),
+			("SP", &self.module.name_sp),
+			("TW", &self.module.name_tw),
+		]
+		.into_iter()
+		.filter_map(|(language, name)| Some((language, name.as_ref()?)))
+		.collect()
+	}
+
+	pub fn cos_url(&self) -> Option<String> {
+		COS_CHARAS
+			.iter()
+			.find(|(_, chara)| *chara == self.module.chara)
+			.map(|(name, _)| format!("/cos/{name}/{}", self.module.cos.id))
+	}
+}
+
 #[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct CstmItem {
 	pub uid: String,
@@ -1372,7 +2714,8 @@ impl PvSearch {
 	get,
 	path = "/api/v1/ids/pvs",
 	params(
-		SearchParams
+		SearchParams,
+		PvFilters
 	),
 	responses(
 		(status = 200, body = PvSearch, content_type = "application/json"),
@@ -1381,6 +2724,7 @@ impl PvSearch {
 )]
 pub async fn search_pvs(
 	Query(query): Query<SearchParams>,
+	Query(pv_filters): Query<PvFilters>,
 	State(state): State<AppState>,
 ) -> Result<Json<PvSearch>, (StatusCode, String)> {
 	let index = state.meilisearch.index("pvs");
@@ -1392,11 +2736,13 @@ pub async fn search_pvs(
 
 	search.sort = Some(&["pv_id:asc"]);
 
-	let filter = if let Some(filter) = &query.filter {
-		format!("{filter}")
-	} else {
-		String::new()
-	};
+	let filter = query
+		.filter
+		.iter()
+		.map(|filter| format!("({filter})"))
+		.chain(pv_filters.filters())
+		.intersperse(String::from(" AND "))
+		.collect::<String>();
 
 	search.filter = Some(meilisearch_sdk::search::Filter::new(sqlx::Either::Left(
 		filter.as_str(),
@@ -1457,11 +2803,14 @@ pub async fn search_pvs(
 		vec.push(Pv {
 			uid: BASE64_STANDARD.encode(pv.uid.to_ne_bytes()),
 			id: pv.pv_id,
+			preview_url: pv.preview_url(),
 			name: pv.song_name,
 			name_en: pv.song_name_en,
 			song_info: pv.song_info,
 			song_info_en: pv.song_info_en,
 			levels: pv.levels,
+			charts: pv.charts,
+			metadata: pv.metadata,
 			post,
 		})
 	}
@@ -1531,6 +2880,122 @@ pub async fn search_pvs(
 	}))
 }
 
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PvDetail {
+	pub pv: Pv,
+	/// Other posts and the base game shipping the same pv id
+	pub other_pvs: Vec<Pv>,
+	pub nc_songs: Vec<NcSong>,
+	pub reservations: Vec<Reservation>,
+	pub posts: BTreeMap<i32, Post>,
+	pub users: BTreeMap<i64, User>,
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/ids/pvs/{post}/{pv_id}",
+	params(
+		("post" = i32, Path, description = "Post id, or -1 for the base game"),
+		("pv_id" = i32, Path)
+	),
+	responses(
+		(status = 200, body = PvDetail, content_type = "application/json"),
+		(status = 400, body = String),
+		(status = 404, body = String)
+	)
+)]
+pub async fn pv_detail(
+	Path((post, pv_id)): Path<(i32, i32)>,
+	user: Result<User, ErrorTemplate>,
+	State(state): State<AppState>,
+) -> Result<Json<PvDetail>, (StatusCode, String)> {
+	let Json(mut pvs) = search_pvs(
+		Query(SearchParams {
+			query: None,
+			filter: Some(format!("pv_id={pv_id}")),
+			limit: Some(u32::MAX as usize),
+			offset: Some(0),
+		}),
+		Query(PvFilters::default()),
+		State(state.clone()),
+	)
+	.await?;
+
+	// Entries of private posts are only shown to their authors and admins
+	let user = user.ok();
+	pvs.posts
+		.retain(|_, post| post.visible_to(user.as_ref(), &state.config));
+	let visible = |post: i32| post == -1 || pvs.posts.contains_key(&post);
+
+	let (mut pv, other_pvs): (Vec<_>, Vec<_>) = pvs
+		.pvs
+		.into_iter()
+		.filter(|pv| visible(pv.post.unwrap_or(-1)))
+		.partition(|pv| pv.post.unwrap_or(-1) == post);
+	let Some(pv) = pv.pop() else {
+		return Err((StatusCode::NOT_FOUND, String::from("PV not found")));
+	};
+
+	let mut users = BTreeMap::new();
+	let reservations = sqlx::query!(
+		r#"
+		SELECT r.reservation_type, r.time, rl.label as "label?",
+			u.id, u.name, u.avatar, u.display_name, u.public_likes, u.theme, u.show_explicit
+		FROM reservations r
+		INNER JOIN users u ON r.user_id = u.id
+		LEFT JOIN reservation_labels rl ON rl.reservation_type = r.reservation_type AND rl.id = $2 AND rl.user_id = r.user_id
+		WHERE r.reservation_type = $1
+		AND r.range_start <= $2
+		AND r.range_start + r.length > $2
+		"#,
+		ReservationType::Song as i32,
+		pv_id
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default()
+	.into_iter()
+	.map(|reservation| {
+		users.insert(
+			reservation.id,
+			User {
+				id: reservation.id,
+				name: reservation.name,
+				avatar: reservation.avatar,
+				display_name: reservation.display_name,
+				public_likes: reservation.public_likes,
+				theme: reservation.theme.into(),
+				show_explicit: reservation.show_explicit,
+			},
+		);
+		Reservation {
+			id: pv_id,
+			user: reservation.id,
+			reservation_type: reservation.reservation_type.into(),
+			time: reservation.time.assume_offset(time::UtcOffset::UTC),
+			label: reservation.label,
+		}
+	})
+	.collect();
+
+	let nc_songs = pvs
+		.nc_songs
+		.remove(&pv.id)
+		.unwrap_or_default()
+		.into_iter()
+		.filter(|song| visible(song.post))
+		.collect();
+
+	Ok(Json(PvDetail {
+		nc_songs,
+		pv,
+		other_pvs,
+		reservations,
+		posts: pvs.posts,
+		users,
+	}))
+}
+
 #[derive(Deserialize)]
 enum MeilisearchEntryOrReservation<T> {
 	#[serde(untagged)]
@@ -1643,11 +3108,14 @@ pub async fn search_pvs_and_reservations(
 				res.pvs.push(EntryOrReservation::Entry(Pv {
 					uid: BASE64_STANDARD.encode(pv.uid.to_ne_bytes()),
 					id: pv.pv_id,
+					preview_url: pv.preview_url(),
 					name: pv.song_name,
 					name_en: pv.song_name_en,
 					song_info: pv.song_info,
 					song_info_en: pv.song_info_en,
 					levels: pv.levels,
+					charts: pv.charts,
+					metadata: pv.metadata,
 					post,
 				}))
 			}
@@ -1755,6 +3223,14 @@ impl ModuleSearch {
 			.iter()
 			.find(|module| module.module.cos.id == id)
 	}
+
+	/// Drops modules of private posts the user can't see
+	pub fn retain_visible(&mut self, user: Option<&User>, config: &crate::Config) {
+		self.posts.retain(|_, post| post.visible_to(user, config));
+		let posts = &self.posts;
+		self.modules
+			.retain(|module| module.post.is_none_or(|post| posts.contains_key(&post)));
+	}
 }
 
 #[utoipa::path(
@@ -1875,6 +3351,16 @@ impl CstmItemSearch {
 	pub fn find_cstm_item(&self, id: i32) -> Option<&CstmItem> {
 		self.cstm_items.iter().find(|cstm_item| cstm_item.id == id)
 	}
+
+	/// Drops customize items and bound modules of private posts the user can't see
+	pub fn retain_visible(&mut self, user: Option<&User>, config: &crate::Config) {
+		self.posts.retain(|_, post| post.visible_to(user, config));
+		let posts = &self.posts;
+		self.cstm_items
+			.retain(|cstm_item| cstm_item.post.is_none_or(|post| posts.contains_key(&post)));
+		self.bound_modules
+			.retain(|_, module| module.post.is_none_or(|post| posts.contains_key(&post)));
+	}
 }
 
 #[utoipa::path(
@@ -2022,43 +3508,243 @@ pub async fn search_cstm_items(
 			.intersperse(String::from(" OR "))
 			.collect::<String>();
 
-		let Json(modules) = crate::api::ids::search_modules(
-			Query(crate::api::ids::SearchParams {
-				query: None,
-				filter: Some(filter),
-				limit: Some(pending_bound_modules.len()),
-				offset: Some(0),
-			}),
-			State(state.clone()),
-		)
-		.await
-		.unwrap_or_default();
+		let Json(modules) = crate::api::ids::search_modules(
+			Query(crate::api::ids::SearchParams {
+				query: None,
+				filter: Some(filter),
+				limit: Some(pending_bound_modules.len()),
+				offset: Some(0),
+			}),
+			State(state.clone()),
+		)
+		.await
+		.unwrap_or_default();
+
+		for module in modules.modules {
+			if let Some(post_id) = &module.post {
+				if let Some(post) = modules.posts.get(post_id) {
+					if !posts.contains_key(post_id) {
+						posts.insert(*post_id, post.clone());
+					}
+				}
+			}
+
+			bound_modules.insert(
+				module.id,
+				Module {
+					uid: module.uid,
+					post: module.post,
+					id: module.id,
+					module: module.module,
+				},
+			);
+		}
+	}
+
+	Ok(Json(CstmItemSearch {
+		cstm_items: vec,
+		bound_modules,
+		posts,
+	}))
+}
+
+/// Character names used in /cos/{chara}/{cos_id} urls
+pub const COS_CHARAS: [(&str, module_db::Chara); 10] = [
+	("miku", module_db::Chara::Miku),
+	("rin", module_db::Chara::Rin),
+	("len", module_db::Chara::Len),
+	("luka", module_db::Chara::Luka),
+	("neru", module_db::Chara::Neru),
+	("haku", module_db::Chara::Haku),
+	("kaito", module_db::Chara::Kaito),
+	("meiko", module_db::Chara::Meiko),
+	("sakine", module_db::Chara::Sakine),
+	("teto", module_db::Chara::Teto),
+];
+
+fn bound_cstm_items_filter(module: &Module) -> String {
+	if let Some(post) = module.post {
+		format!("(bind_module={} AND post_id={post})", module.id)
+	} else {
+		format!("bind_module={}", module.id)
+	}
+}
+
+fn chara_filter(chara: &module_db::Chara) -> String {
+	format!(
+		"chara={}",
+		serde_json::to_string(chara)
+			.unwrap_or_default()
+			.trim_matches('\"')
+	)
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ModuleDetail {
+	pub module: Module,
+	/// Other posts and the base game using the same module id
+	pub same_id: ModuleSearch,
+	/// Modules in any post using the same costume
+	pub same_costume: ModuleSearch,
+	pub cstm_items: CstmItemSearch,
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/ids/modules/{post}/{module_id}",
+	params(
+		("post" = i32, Path, description = "Post id, or -1 for the base game"),
+		("module_id" = i32, Path)
+	),
+	responses(
+		(status = 200, body = ModuleDetail, content_type = "application/json"),
+		(status = 400, body = String),
+		(status = 404, body = String)
+	)
+)]
+pub async fn module_detail(
+	Path((post, module_id)): Path<(i32, i32)>,
+	user: Result<User, ErrorTemplate>,
+	State(state): State<AppState>,
+) -> Result<Json<ModuleDetail>, (StatusCode, String)> {
+	let Json(mut same_id) = search_modules(
+		Query(SearchParams {
+			query: None,
+			filter: Some(format!("module_id={module_id}")),
+			limit: Some(u32::MAX as usize),
+			offset: Some(0),
+		}),
+		State(state.clone()),
+	)
+	.await?;
+
+	// Entries of private posts are only shown to their authors and admins
+	let user = user.ok();
+	same_id.retain_visible(user.as_ref(), &state.config);
+
+	let Some(index) = same_id
+		.modules
+		.iter()
+		.position(|module| module.post.unwrap_or(-1) == post)
+	else {
+		return Err((StatusCode::NOT_FOUND, String::from("Module not found")));
+	};
+	let module = same_id.modules.remove(index);
+
+	let Json(mut same_costume) = search_modules(
+		Query(SearchParams {
+			query: None,
+			filter: Some(format!(
+				"{} AND cos.id={}",
+				chara_filter(&module.module.chara),
+				module.module.cos.id
+			)),
+			limit: Some(u32::MAX as usize),
+			offset: Some(0),
+		}),
+		State(state.clone()),
+	)
+	.await?;
+	same_costume.retain_visible(user.as_ref(), &state.config);
+	same_costume.modules.retain(|other| other.uid != module.uid);
+
+	let Json(mut cstm_items) = search_cstm_items(
+		Query(SearchParams {
+			query: None,
+			filter: Some(bound_cstm_items_filter(&module)),
+			limit: Some(u32::MAX as usize),
+			offset: Some(0),
+		}),
+		State(state.clone()),
+	)
+	.await?;
+	cstm_items.retain_visible(user.as_ref(), &state.config);
+
+	Ok(Json(ModuleDetail {
+		module,
+		same_id,
+		same_costume,
+		cstm_items,
+	}))
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CosDetail {
+	pub chara: module_db::Chara,
+	pub cos_id: i32,
+	pub modules: ModuleSearch,
+	pub cstm_items: CstmItemSearch,
+}
 
-		for module in modules.modules {
-			if let Some(post_id) = &module.post {
-				if let Some(post) = modules.posts.get(post_id) {
-					if !posts.contains_key(post_id) {
-						posts.insert(*post_id, post.clone());
-					}
-				}
-			}
+#[utoipa::path(
+	get,
+	path = "/api/v1/ids/cos/{chara}/{cos_id}",
+	params(
+		("chara" = String, Path, description = "Character name, such as miku"),
+		("cos_id" = i32, Path)
+	),
+	responses(
+		(status = 200, body = CosDetail, content_type = "application/json"),
+		(status = 400, body = String),
+		(status = 404, body = String)
+	)
+)]
+pub async fn cos_detail(
+	Path((chara, cos_id)): Path<(String, i32)>,
+	user: Result<User, ErrorTemplate>,
+	State(state): State<AppState>,
+) -> Result<Json<CosDetail>, (StatusCode, String)> {
+	let Some((_, chara)) = COS_CHARAS
+		.iter()
+		.find(|(name, _)| *name == chara.to_lowercase())
+	else {
+		return Err((StatusCode::NOT_FOUND, String::from("Unknown character")));
+	};
+	let chara = chara.clone();
+
+	let Json(mut modules) = search_modules(
+		Query(SearchParams {
+			query: None,
+			filter: Some(format!("{} AND cos.id={cos_id}", chara_filter(&chara))),
+			limit: Some(u32::MAX as usize),
+			offset: Some(0),
+		}),
+		State(state.clone()),
+	)
+	.await?;
 
-			bound_modules.insert(
-				module.id,
-				Module {
-					uid: module.uid,
-					post: module.post,
-					id: module.id,
-					module: module.module,
-				},
-			);
-		}
+	// Entries of private posts are only shown to their authors and admins
+	let user = user.ok();
+	modules.retain_visible(user.as_ref(), &state.config);
+
+	if modules.modules.is_empty() {
+		return Err((StatusCode::NOT_FOUND, String::from("Costume not found")));
 	}
 
-	Ok(Json(CstmItemSearch {
-		cstm_items: vec,
-		bound_modules,
-		posts,
+	let Json(mut cstm_items) = search_cstm_items(
+		Query(SearchParams {
+			query: None,
+			filter: Some(
+				modules
+					.modules
+					.iter()
+					.map(bound_cstm_items_filter)
+					.intersperse(String::from(" OR "))
+					.collect::<String>(),
+			),
+			limit: Some(u32::MAX as usize),
+			offset: Some(0),
+		}),
+		State(state.clone()),
+	)
+	.await?;
+	cstm_items.retain_visible(user.as_ref(), &state.config);
+
+	Ok(Json(CosDetail {
+		chara,
+		cos_id,
+		modules,
+		cstm_items,
 	}))
 }
 
@@ -2240,11 +3926,14 @@ pub async fn search_nc_songs(
 					pv_vec.push(Pv {
 						uid: BASE64_STANDARD.encode(pv.uid.to_ne_bytes()),
 						id: pv.pv_id,
+						preview_url: pv.preview_url(),
 						name: pv.song_name,
 						name_en: pv.song_name_en,
 						song_info: pv.song_info,
 						song_info_en: pv.song_info_en,
 						levels: pv.levels,
+						charts: pv.charts,
+						metadata: pv.metadata,
 						post,
 					});
 				} else {
@@ -2253,11 +3942,14 @@ pub async fn search_nc_songs(
 						vec![Pv {
 							uid: BASE64_STANDARD.encode(pv.uid.to_ne_bytes()),
 							id: pv.pv_id,
+							preview_url: pv.preview_url(),
 							name: pv.song_name,
 							name_en: pv.song_name_en,
 							song_info: pv.song_info,
 							song_info_en: pv.song_info_en,
 							levels: pv.levels,
+							charts: pv.charts,
+							metadata: pv.metadata,
 							post,
 						}],
 					);
@@ -2375,11 +4067,13 @@ pub async fn create_reservation(
 	)
 	.await;
 
+	// Only ids whose insert succeeded are logged
+	let mut reserved = Vec::new();
 	match validity {
 		ReserveRangeResult::ValidRange => {
 			let now = time::OffsetDateTime::now_utc();
 			let time = time::PrimitiveDateTime::new(now.date(), now.time());
-			_ = sqlx::query!(
+			if sqlx::query!(
 				"INSERT INTO reservations VALUES($1, $2, $3, $4, $5)",
 				user.id,
 				query.reservation_type as i32,
@@ -2388,7 +4082,12 @@ pub async fn create_reservation(
 				time
 			)
 			.execute(&state.db)
-			.await;
+			.await
+			.is_ok()
+			{
+				reserved.extend(query.start..(query.start + query.length));
+			}
+			log_reserved(&reserved, &query, &user, &state).await;
 		}
 		ReserveRangeResult::PartialValidRange(ref old_ids) => {
 			let old_ids = old_ids.iter().cloned().collect::<BTreeSet<_>>();
@@ -2422,7 +4121,7 @@ pub async fn create_reservation(
 			}
 
 			for reservation in ranges {
-				_ = sqlx::query!(
+				if sqlx::query!(
 					"INSERT INTO reservations VALUES($1, $2, $3, $4, $5)",
 					reservation.user.id,
 					reservation.reservation_type as i32,
@@ -2431,9 +4130,17 @@ pub async fn create_reservation(
 					time::PrimitiveDateTime::new(reservation.time.date(), reservation.time.time()),
 				)
 				.execute(&state.db)
-				.await;
+				.await
+				.is_ok()
+				{
+					reserved.extend(
+						reservation.range_start..(reservation.range_start + reservation.length),
+					);
+				}
 			}
 
+			// Logged before optimising so any ids it unreserves again come after
+			log_reserved(&reserved, &query, &user, &state).await;
 			optimise_reservations(query.reservation_type, state).await;
 		}
 		_ => {}
@@ -2442,6 +4149,22 @@ pub async fn create_reservation(
 	Json(validity)
 }
 
+async fn log_reserved(ids: &[i32], query: &ReserveRangeArgs, user: &User, state: &AppState) {
+	let ids = ids
+		.iter()
+		.map(|id| (*id, String::new()))
+		.collect::<Vec<_>>();
+	crate::api::changes::log_id_changes(
+		query.reservation_type,
+		crate::api::changes::IdChangeKind::Reserved,
+		&ids,
+		None,
+		Some(user.id),
+		state,
+	)
+	.await;
+}
+
 pub async fn delete_reservation_admin(
 	axum::extract::Path(user_id): axum::extract::Path<i64>,
 	user: User,
@@ -2575,7 +4298,22 @@ pub async fn delete_reservation(
 			.await;
 		}
 
-		_ = transaction.commit().await;
+		if transaction.commit().await.is_ok() {
+			let unreserved = ids
+				.into_iter()
+				.filter(|id| reservered_ids.contains_key(id))
+				.map(|id| (id, String::new()))
+				.collect::<Vec<_>>();
+			crate::api::changes::log_id_changes(
+				query.reservation_type,
+				crate::api::changes::IdChangeKind::Unreserved,
+				&unreserved,
+				None,
+				Some(user.id),
+				&state,
+			)
+			.await;
+		}
 	}
 }
 
@@ -2614,7 +4352,7 @@ pub async fn label_reservation(
 		return StatusCode::BAD_REQUEST;
 	}
 
-	if sqlx::query!(
+	let updated = sqlx::query!(
 		r#"
 		UPDATE reservation_labels SET label=$1 WHERE user_id=$2 AND reservation_type=$3 AND id=$4
 		"#,
@@ -2625,11 +4363,10 @@ pub async fn label_reservation(
 	)
 	.execute(&state.db)
 	.await
-	.unwrap_or_default()
-	.rows_affected()
-		== 0
-	{
-		_ = sqlx::query!(
+	.is_ok_and(|result| result.rows_affected() > 0);
+
+	let labelled = updated
+		|| sqlx::query!(
 			"INSERT INTO reservation_labels VALUES ($1, $2, $3, $4)",
 			user.id,
 			query.reservation_type as i32,
@@ -2637,9 +4374,23 @@ pub async fn label_reservation(
 			query.label
 		)
 		.execute(&state.db)
-		.await;
+		.await
+		.is_ok();
+
+	if !labelled {
+		return StatusCode::INTERNAL_SERVER_ERROR;
 	}
 
+	crate::api::changes::log_id_changes(
+		query.reservation_type,
+		crate::api::changes::IdChangeKind::Labelled,
+		&[(id, query.label)],
+		None,
+		Some(user.id),
+		&state,
+	)
+	.await;
+
 	StatusCode::OK
 }
 
@@ -2694,72 +4445,45 @@ pub async fn check_reserve_range(
 	}
 
 	let conflicts = match reservation_type {
-		ReservationType::Song => {
-			let index = state.meilisearch.index("pvs");
-
-			let filter = (start..(start + length))
-				.map(|id| format!("pv_id={id}"))
-				.intersperse(String::from(" OR "))
-				.collect::<String>();
-
-			let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-				.with_limit(u32::MAX as usize)
-				.with_filter(&filter)
-				.execute::<MeilisearchPv>()
-				.await;
-
-			search.map_or(BTreeMap::new(), |search| {
-				search
-					.results
-					.into_iter()
-					.map(|pv| (pv.pv_id, pv.post))
-					.collect::<BTreeMap<_, _>>()
-			})
-		}
-		ReservationType::Module => {
-			let index = state.meilisearch.index("modules");
-
-			let filter = (start..(start + length))
-				.map(|id| format!("module_id={id}"))
-				.intersperse(String::from(" OR "))
-				.collect::<String>();
-
-			let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-				.with_limit(u32::MAX as usize)
-				.with_filter(&filter)
-				.execute::<MeilisearchModule>()
-				.await;
-
-			search.map_or(BTreeMap::new(), |search| {
-				search
-					.results
-					.into_iter()
-					.map(|module| (module.module_id, module.post_id))
-					.collect::<BTreeMap<_, _>>()
-			})
-		}
-		ReservationType::CstmItem => {
-			let index = state.meilisearch.index("cstm_items");
-
-			let filter = (start..(start + length))
-				.map(|id| format!("customize_item_id={id}"))
-				.intersperse(String::from(" OR "))
-				.collect::<String>();
-
-			let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-				.with_limit(u32::MAX as usize)
-				.with_filter(&filter)
-				.execute::<MeilisearchCstmItem>()
-				.await;
-
-			search.map_or(BTreeMap::new(), |search| {
-				search
-					.results
-					.into_iter()
-					.map(|cstm_item| (cstm_item.customize_item_id, cstm_item.post_id))
-					.collect::<BTreeMap<_, _>>()
-			})
-		}
+		ReservationType::Song => sqlx::query!(
+			"SELECT pv_id as id, post_id FROM pvs WHERE pv_id >= $1 AND pv_id < $2",
+			start,
+			start + length
+		)
+		.fetch_all(&state.db)
+		.await
+		.map(|conflicts| {
+			conflicts
+				.into_iter()
+				.map(|conflict| (conflict.id, conflict.post_id))
+				.collect::<Vec<_>>()
+		}),
+		ReservationType::Module => sqlx::query!(
+			"SELECT module_id as id, post_id FROM modules WHERE module_id >= $1 AND module_id < $2",
+			start,
+			start + length
+		)
+		.fetch_all(&state.db)
+		.await
+		.map(|conflicts| {
+			conflicts
+				.into_iter()
+				.map(|conflict| (conflict.id, conflict.post_id))
+				.collect::<Vec<_>>()
+		}),
+		ReservationType::CstmItem => sqlx::query!(
+			"SELECT customize_item_id as id, post_id FROM cstm_items WHERE customize_item_id >= $1 AND customize_item_id < $2",
+			start,
+			start + length
+		)
+		.fetch_all(&state.db)
+		.await
+		.map(|conflicts| {
+			conflicts
+				.into_iter()
+				.map(|conflict| (conflict.id, conflict.post_id))
+				.collect::<Vec<_>>()
+		}),
 		ReservationType::CosMiku
 		| ReservationType::CosRin
 		| ReservationType::CosLen
@@ -2771,32 +4495,28 @@ pub async fn check_reserve_range(
 		| ReservationType::CosSakine
 		| ReservationType::CosTeto => {
 			let chara = module_db::Chara::try_from(reservation_type as i32 - 10).unwrap();
-			let index = state.meilisearch.index("modules");
-
-			let filter = (start..(start + length))
-				.map(|id| format!("cos.id={id}"))
-				.intersperse(String::from(" OR "))
-				.collect::<String>();
-
-			let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-				.with_limit(u32::MAX as usize)
-				.with_filter(&format!(
-					"({filter}) AND chara={}",
-					serde_json::to_string(&chara).unwrap().trim_matches('\"'),
-				))
-				.execute::<MeilisearchModule>()
-				.await;
-
-			search.map_or(BTreeMap::new(), |search| {
-				search
-					.results
+			let chara = serde_json::to_string(&chara).unwrap();
+			sqlx::query!(
+				"SELECT cos_id as id, post_id FROM modules WHERE chara = $3 AND cos_id >= $1 AND cos_id < $2",
+				start,
+				start + length,
+				chara.trim_matches('\"'),
+			)
+			.fetch_all(&state.db)
+			.await
+			.map(|conflicts| {
+				conflicts
 					.into_iter()
-					.map(|module| (module.module.cos.id, module.post_id))
-					.collect::<BTreeMap<_, _>>()
+					.map(|conflict| (conflict.id, conflict.post_id))
+					.collect::<Vec<_>>()
 			})
 		}
 	};
 
+	let Ok(conflicts) = conflicts else {
+		return ReserveRangeResult::InvalidRange;
+	};
+
 	let mut partial_range = Vec::new();
 	for (id, post) in conflicts {
 		if post == -1 {
@@ -2834,6 +4554,9 @@ pub async fn check_reserve_range(
 		}
 	}
 
+	partial_range.sort();
+	partial_range.dedup();
+
 	if partial_range == ((start)..(start + length)).collect::<Vec<_>>() {
 		return ReserveRangeResult::InvalidRange;
 	}
@@ -2859,76 +4582,48 @@ pub async fn get_user_uploads(
 	.unwrap_or_default();
 
 	if user_posts.len() > 0 {
-		match reservation_type {
-			ReservationType::Song => {
-				let index = state.meilisearch.index("pvs");
-
-				let filter = user_posts
-					.iter()
-					.map(|post| format!("post={}", post.post_id))
-					.intersperse(String::from(" OR "))
-					.collect::<String>();
-
-				let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-					.with_limit(u32::MAX as usize)
-					.with_filter(&filter)
-					.execute::<MeilisearchPv>()
-					.await;
-
-				search.map_or(BTreeSet::new(), |search| {
-					search
-						.results
-						.into_iter()
-						.map(|pv| pv.pv_id)
-						.collect::<BTreeSet<_>>()
-				})
-			}
-			ReservationType::Module => {
-				let index = state.meilisearch.index("modules");
-
-				let filter = user_posts
-					.iter()
-					.map(|post| format!("post_id={}", post.post_id))
-					.intersperse(String::from(" OR "))
-					.collect::<String>();
-
-				let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-					.with_limit(u32::MAX as usize)
-					.with_filter(&filter)
-					.execute::<MeilisearchModule>()
-					.await;
-
-				search.map_or(BTreeSet::new(), |search| {
-					search
-						.results
-						.into_iter()
-						.map(|module| module.module_id)
-						.collect::<BTreeSet<_>>()
-				})
-			}
-			ReservationType::CstmItem => {
-				let index = state.meilisearch.index("cstm_items");
-
-				let filter = user_posts
-					.iter()
-					.map(|post| format!("post_id={}", post.post_id))
-					.intersperse(String::from(" OR "))
-					.collect::<String>();
-
-				let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-					.with_limit(u32::MAX as usize)
-					.with_filter(&filter)
-					.execute::<MeilisearchCstmItem>()
-					.await;
+		let user_posts = user_posts
+			.iter()
+			.map(|post| post.post_id)
+			.collect::<Vec<_>>();
 
-				search.map_or(BTreeSet::new(), |search| {
-					search
-						.results
-						.into_iter()
-						.map(|cstm_item| cstm_item.customize_item_id)
-						.collect::<BTreeSet<_>>()
-				})
-			}
+		let ids = match reservation_type {
+			ReservationType::Song => sqlx::query!(
+				"SELECT pv_id as id FROM pvs WHERE post_id = ANY($1)",
+				&user_posts
+			)
+			.fetch_all(&state.db)
+			.await
+			.map(|uploads| {
+				uploads
+					.into_iter()
+					.map(|upload| upload.id)
+					.collect::<BTreeSet<_>>()
+			}),
+			ReservationType::Module => sqlx::query!(
+				"SELECT module_id as id FROM modules WHERE post_id = ANY($1)",
+				&user_posts
+			)
+			.fetch_all(&state.db)
+			.await
+			.map(|uploads| {
+				uploads
+					.into_iter()
+					.map(|upload| upload.id)
+					.collect::<BTreeSet<_>>()
+			}),
+			ReservationType::CstmItem => sqlx::query!(
+				"SELECT customize_item_id as id FROM cstm_items WHERE post_id = ANY($1)",
+				&user_posts
+			)
+			.fetch_all(&state.db)
+			.await
+			.map(|uploads| {
+				uploads
+					.into_iter()
+					.map(|upload| upload.id)
+					.collect::<BTreeSet<_>>()
+			}),
 			ReservationType::CosMiku
 			| ReservationType::CosRin
 			| ReservationType::CosLen
@@ -2940,32 +4635,24 @@ pub async fn get_user_uploads(
 			| ReservationType::CosSakine
 			| ReservationType::CosTeto => {
 				let chara = module_db::Chara::try_from(reservation_type as i32 - 10).unwrap();
-				let index = state.meilisearch.index("modules");
-
-				let filter = user_posts
-					.iter()
-					.map(|post| format!("post_id={}", post.post_id))
-					.intersperse(String::from(" OR "))
-					.collect::<String>();
-
-				let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-					.with_limit(u32::MAX as usize)
-					.with_filter(&format!(
-						"({filter}) AND chara={}",
-						serde_json::to_string(&chara).unwrap().trim_matches('\"'),
-					))
-					.execute::<MeilisearchModule>()
-					.await;
-
-				search.map_or(BTreeSet::new(), |search| {
-					search
-						.results
+				let chara = serde_json::to_string(&chara).unwrap();
+				sqlx::query!(
+					"SELECT cos_id as id FROM modules WHERE post_id = ANY($1) AND chara = $2",
+					&user_posts,
+					chara.trim_matches('\"'),
+				)
+				.fetch_all(&state.db)
+				.await
+				.map(|uploads| {
+					uploads
 						.into_iter()
-						.map(|module| module.module.cos.id)
+						.map(|upload| upload.id)
 						.collect::<BTreeSet<_>>()
 				})
 			}
-		}
+		};
+
+		ids.unwrap_or_default()
 	} else {
 		BTreeSet::new()
 	}
@@ -3186,129 +4873,7 @@ pub async fn optimise_reservations(reservation_type: ReservationType, state: App
 		})
 		.collect::<BTreeMap<_, _>>();
 
-		let user_posts = sqlx::query!(
-			r#"
-			SELECT post_id
-			FROM post_authors
-			WHERE user_id = $1
-			"#,
-			user.id,
-		)
-		.fetch_all(&state.db)
-		.await
-		.unwrap_or_default();
-
-		let ids = if user_posts.len() > 0 {
-			match reservation_type {
-				ReservationType::Song => {
-					let index = state.meilisearch.index("pvs");
-
-					let filter = user_posts
-						.iter()
-						.map(|post| format!("post={}", post.post_id))
-						.intersperse(String::from(" OR "))
-						.collect::<String>();
-
-					let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-						.with_limit(u32::MAX as usize)
-						.with_filter(&filter)
-						.execute::<MeilisearchPv>()
-						.await;
-
-					search.map_or(BTreeSet::new(), |search| {
-						search
-							.results
-							.into_iter()
-							.map(|pv| pv.pv_id)
-							.collect::<BTreeSet<_>>()
-					})
-				}
-				ReservationType::Module => {
-					let index = state.meilisearch.index("modules");
-
-					let filter = user_posts
-						.iter()
-						.map(|post| format!("post_id={}", post.post_id))
-						.intersperse(String::from(" OR "))
-						.collect::<String>();
-
-					let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-						.with_limit(u32::MAX as usize)
-						.with_filter(&filter)
-						.execute::<MeilisearchModule>()
-						.await;
-
-					search.map_or(BTreeSet::new(), |search| {
-						search
-							.results
-							.into_iter()
-							.map(|module| module.module_id)
-							.collect::<BTreeSet<_>>()
-					})
-				}
-				ReservationType::CstmItem => {
-					let index = state.meilisearch.index("cstm_items");
-
-					let filter = user_posts
-						.iter()
-						.map(|post| format!("post_id={}", post.post_id))
-						.intersperse(String::from(" OR "))
-						.collect::<String>();
-
-					let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-						.with_limit(u32::MAX as usize)
-						.with_filter(&filter)
-						.execute::<MeilisearchCstmItem>()
-						.await;
-
-					search.map_or(BTreeSet::new(), |search| {
-						search
-							.results
-							.into_iter()
-							.map(|cstm_item| cstm_item.customize_item_id)
-							.collect::<BTreeSet<_>>()
-					})
-				}
-				ReservationType::CosMiku
-				| ReservationType::CosRin
-				| ReservationType::CosLen
-				| ReservationType::CosLuka
-				| ReservationType::CosNeru
-				| ReservationType::CosHaku
-				| ReservationType::CosKaito
-				| ReservationType::CosMeiko
-				| ReservationType::CosSakine
-				| ReservationType::CosTeto => {
-					let chara = module_db::Chara::try_from(reservation_type as i32 - 10).unwrap();
-					let index = state.meilisearch.index("modules");
-
-					let filter = user_posts
-						.iter()
-						.map(|post| format!("post_id={}", post.post_id))
-						.intersperse(String::from(" OR "))
-						.collect::<String>();
-
-					let search = meilisearch_sdk::documents::DocumentsQuery::new(&index)
-						.with_limit(u32::MAX as usize)
-						.with_filter(&format!(
-							"({filter}) AND chara={}",
-							serde_json::to_string(&chara).unwrap().trim_matches('\"'),
-						))
-						.execute::<MeilisearchModule>()
-						.await;
-
-					search.map_or(BTreeSet::new(), |search| {
-						search
-							.results
-							.into_iter()
-							.map(|module| module.module.cos.id)
-							.collect::<BTreeSet<_>>()
-					})
-				}
-			}
-		} else {
-			BTreeSet::new()
-		};
+		let ids = get_user_uploads(reservation_type, &user, &state).await;
 
 		let mut ranges: Vec<ReservationRange> = Vec::new();
 		for id in reservered_ids
@@ -3343,8 +4908,15 @@ pub async fn optimise_reservations(reservation_type: ReservationType, state: App
 			}
 		}
 
+		// Ids the user has since used in their own posts, they stop being reserved
+		let unreserved = reservered_ids
+			.keys()
+			.filter(|id| ids.contains(id))
+			.map(|id| (*id, String::new()))
+			.collect::<Vec<_>>();
+
 		if let Ok(mut transaction) = state.db.begin().await {
-			_ = sqlx::query!(
+			let mut ok = sqlx::query!(
 				r#"
 				DELETE FROM reservations r
 				WHERE r.reservation_type = $1
@@ -3354,10 +4926,11 @@ pub async fn optimise_reservations(reservation_type: ReservationType, state: App
 				user.id,
 			)
 			.execute(&mut *transaction)
-			.await;
+			.await
+			.is_ok();
 
 			for reservation in ranges {
-				_ = sqlx::query!(
+				ok = ok && sqlx::query!(
 				"INSERT INTO reservations(user_id, reservation_type, range_start, length, time) VALUES($1, $2, $3, $4, $5)",
 				reservation.user.id,
 				reservation.reservation_type as i32,
@@ -3366,10 +4939,22 @@ pub async fn optimise_reservations(reservation_type: ReservationType, state: App
 				time::PrimitiveDateTime::new(reservation.time.date(), reservation.time.time()),
 			)
 			.execute(&mut *transaction)
-			.await;
+			.await
+			.is_ok();
 			}
 
-			_ = transaction.commit().await;
+			// Dropping the transaction without committing rolls every change back
+			if ok && transaction.commit().await.is_ok() {
+				crate::api::changes::log_id_changes(
+					reservation_type,
+					crate::api::changes::IdChangeKind::Unreserved,
+					&unreserved,
+					None,
+					Some(user.id),
+					&state,
+				)
+				.await;
+			}
 		}
 	}
 
@@ -3551,11 +5136,14 @@ pub async fn all_pvs(State(state): State<AppState>) -> Result<Json<AllPvs>, (Sta
 			let pv = Pv {
 				uid: BASE64_STANDARD.encode(pv.uid.to_ne_bytes()),
 				id: pv.pv_id,
+				preview_url: pv.preview_url(),
 				name: pv.song_name,
 				name_en: pv.song_name_en,
 				song_info: pv.song_info,
 				song_info_en: pv.song_info_en,
 				levels: pv.levels,
+				charts: pv.charts,
+				metadata: pv.metadata,
 				post,
 			};
 
@@ -0,0 +1,756 @@
+use crate::AppState;
+use crate::api::ids::*;
+use crate::api::posts::*;
+use crate::models::*;
+use axum::{extract::*, http::StatusCode};
+use itertools::*;
+use serde::{Deserialize, Serialize};
+use std::collections::*;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ToSchema)]
+pub enum LintSeverity {
+	Info,
+	Warning,
+	Error,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct Lint {
+	pub rule: String,
+	pub severity: LintSeverity,
+	pub ids: Vec<i64>,
+	pub explanation: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct LintReport {
+	pub post_id: i32,
+	pub lints: Vec<Lint>,
+	pub admin_override: bool,
+	#[serde(with = "time::serde::rfc3339")]
+	pub time: time::OffsetDateTime,
+}
+
+impl LintReport {
+	pub fn blocks_publishing(&self) -> bool {
+		!self.admin_override
+			&& self
+				.lints
+				.iter()
+				.any(|lint| lint.severity == LintSeverity::Error)
+	}
+}
+
+fn reservation_lint(
+	rule: &str,
+	kind: &str,
+	reservations: &BTreeMap<i64, BTreeMap<i32, String>>,
+	detail: &PostDetail,
+) -> Option<Lint> {
+	if reservations.is_empty() {
+		return None;
+	}
+
+	let users = reservations
+		.keys()
+		.filter_map(|user| detail.conflict_users.get(user))
+		.map(|user| user.display_name.clone())
+		.intersperse(String::from(", "))
+		.collect::<String>();
+
+	Some(Lint {
+		rule: String::from(rule),
+		severity: LintSeverity::Error,
+		ids: reservations
+			.values()
+			.flat_map(|ids| ids.keys())
+			.map(|id| *id as i64)
+			.unique()
+			.collect(),
+		explanation: format!(
+			"{kind} ids reserved by {users} are used, this post cannot be made public until they are changed or an admin allows it"
+		),
+	})
+}
+
+fn conflict_lint<T>(rule: &str, kind: &str, conflicts: &BTreeMap<i32, Vec<T>>) -> Option<Lint> {
+	if conflicts.is_empty() {
+		return None;
+	}
+
+	Some(Lint {
+		rule: String::from(rule),
+		severity: LintSeverity::Warning,
+		ids: conflicts.keys().map(|id| *id as i64).collect(),
+		explanation: format!(
+			"{kind} ids are also used by other mods or the base game and will conflict with them"
+		),
+	})
+}
+
+pub fn lint_post_detail(detail: &PostDetail) -> Vec<Lint> {
+	let mut lints = Vec::new();
+
+	lints.extend(reservation_lint(
+		"reserved_pv_ids",
+		"PV",
+		&detail.conflicting_pv_reservations,
+		detail,
+	));
+	lints.extend(reservation_lint(
+		"reserved_module_ids",
+		"Module",
+		&detail.conflicting_module_reservations,
+		detail,
+	));
+	lints.extend(reservation_lint(
+		"reserved_cstm_item_ids",
+		"Customize item",
+		&detail.conflicting_cstm_item_reservations,
+		detail,
+	));
+	for (chara, reservations) in &detail.conflicting_costume_reservations {
+		lints.extend(reservation_lint(
+			"reserved_costume_ids",
+			&format!("{} costume", chara.to_string()),
+			reservations,
+			detail,
+		));
+	}
+
+	lints.extend(conflict_lint(
+		"conflicting_pv_ids",
+		"PV",
+		&detail.conflicting_pvs,
+	));
+	lints.extend(conflict_lint(
+		"conflicting_module_ids",
+		"Module",
+		&detail.conflicting_modules,
+	));
+	lints.extend(conflict_lint(
+		"conflicting_cstm_item_ids",
+		"Customize item",
+		&detail.conflicting_cstm_items,
+	));
+
+	let sprites = detail.sprites.values().collect::<BTreeSet<_>>();
+
+	let missing_pv_sprites = detail
+		.pvs
+		.pvs
+		.iter()
+		.filter(|pv| {
+			[
+				format!("SPR_SEL_PVTMB_{:03}", pv.id),
+				format!("SPR_SEL_PV{:03}_SONG_BG{:03}", pv.id, pv.id),
+				format!("SPR_SEL_PV{:03}_SONG_LOGO{:03}", pv.id, pv.id),
+				format!("SPR_SEL_PV{:03}_SONG_JK{:03}", pv.id, pv.id),
+			]
+			.iter()
+			.any(|sprite| !sprites.contains(sprite))
+		})
+		.map(|pv| pv.id as i64)
+		.collect::<Vec<_>>();
+	if !missing_pv_sprites.is_empty() {
+		lints.push(Lint {
+			rule: String::from("missing_pv_sprites"),
+			severity: LintSeverity::Warning,
+			ids: missing_pv_sprites,
+			explanation: String::from(
+				"PVs are missing a thumbnail, background, logo or jacket sprite",
+			),
+		});
+	}
+
+	let missing_module_sprites = detail
+		.modules
+		.modules
+		.iter()
+		.filter(|module| !sprites.contains(&format!("SPR_SEL_MD{:03}CMN_MD_IMG", module.id)))
+		.map(|module| module.id as i64)
+		.collect::<Vec<_>>();
+	if !missing_module_sprites.is_empty() {
+		lints.push(Lint {
+			rule: String::from("missing_module_sprites"),
+			severity: LintSeverity::Warning,
+			ids: missing_module_sprites,
+			explanation: String::from("Modules are missing their selection image sprite"),
+		});
+	}
+
+	let missing_ftc_sprites = detail
+		.modules
+		.modules
+		.iter()
+		.filter(|module| !sprites.contains(&format!("SPR_SEL_MD{:03}CMN_MD_IMG_FT", module.id)))
+		.map(|module| module.id as i64)
+		.chain(
+			detail
+				.cstm_items
+				.cstm_items
+				.iter()
+				.filter(|cstm_item| {
+					!sprites.contains(&format!("SPR_CMNITM_THMB{:03}_ITM_IMG_FT", cstm_item.id))
+				})
+				.map(|cstm_item| cstm_item.id as i64),
+		)
+		.collect::<Vec<_>>();
+	if !missing_ftc_sprites.is_empty() {
+		lints.push(Lint {
+			rule: String::from("missing_ftc_sprites"),
+			severity: LintSeverity::Info,
+			ids: missing_ftc_sprites,
+			explanation: String::from(
+				"Modules or customize items have no Future Tone style sprites, the default ones will be shown in the Future Tone UI",
+			),
+		});
+	}
+
+	if !detail.has_dml_pvtmb {
+		lints.push(Lint {
+			rule: String::from("pvtmb_not_in_own_set"),
+			severity: LintSeverity::Warning,
+			ids: Vec::new(),
+			explanation: String::from(
+				"PV thumbnails should be in their own SPR_SEL_PVTMB_ sprite sets so DML can load them",
+			),
+		});
+	}
+
+	let expatch_pvs = detail
+		.pvs
+		.pvs
+		.iter()
+		.filter(|pv| (pv.levels[3].is_some() || pv.levels[4].is_some()) && pv.levels[2].is_none())
+		.map(|pv| pv.id as i64)
+		.collect::<Vec<_>>();
+	if !expatch_pvs.is_empty() {
+		lints.push(Lint {
+			rule: String::from("requires_expatch"),
+			severity: LintSeverity::Info,
+			ids: expatch_pvs,
+			explanation: String::from(
+				"PVs have an extreme chart without a hard chart and require ExPatch to be selectable",
+			),
+		});
+	}
+
+	if detail.requires_nc {
+		lints.push(Lint {
+			rule: String::from("requires_nc"),
+			severity: LintSeverity::Info,
+			ids: detail
+				.nc_songs
+				.nc_songs
+				.iter()
+				.map(|nc_song| nc_song.pv_id as i64)
+				.collect(),
+			explanation: String::from("New Classics is required to play some charts"),
+		});
+	}
+
+	lints
+}
+
+pub struct ArchiveFile<T> {
+	/// The included mod folder relative to the archive root, empty for base game dumps
+	pub folder: String,
+	/// One of ROM_DIRS
+	pub rom: &'static str,
+	pub prefix: &'static str,
+	pub file: &'static str,
+	pub data: T,
+}
+
+impl<T> ArchiveFile<T> {
+	pub fn name(&self) -> String {
+		let file = format!("{}{}", self.prefix, self.file);
+		[self.folder.as_str(), self.rom, "rom", file.as_str()]
+			.into_iter()
+			.filter(|part| !part.is_empty() && *part != ".")
+			.join("/")
+	}
+
+	/// Identifies the file on disk, ROM_DIRS lists some folders twice
+	fn key(&self) -> (&str, &str, &str, &str) {
+		(&self.folder, self.rom, self.prefix, self.file)
+	}
+}
+
+// Everything parsed from a posts archive before base game entries are filtered out
+#[derive(Default)]
+pub struct ArchiveContents {
+	pub pv_dbs: Vec<ArchiveFile<pv_db::PvDb>>,
+	pub module_dbs: Vec<ArchiveFile<module_db::ModuleDb>>,
+	pub db_entries: Vec<ArchiveFile<BTreeMap<u32, String>>>,
+	pub objsets: BTreeSet<String>,
+	pub sprites: BTreeSet<String>,
+	pub scripts: BTreeSet<String>,
+	pub sprite_locations: BTreeMap<String, crate::thumbnails::SpriteLocation>,
+}
+
+pub struct ArchiveLintContext {
+	pub archive: ArchiveContents,
+	pub base_objsets: BTreeSet<String>,
+	pub base_sprites: BTreeSet<String>,
+	pub base_scripts: BTreeSet<String>,
+}
+
+impl ArchiveLintContext {
+	pub fn has_objset(&self, name: &str) -> bool {
+		self.archive.objsets.contains(name) || self.base_objsets.contains(name)
+	}
+
+	pub fn has_sprite(&self, name: &str) -> bool {
+		self.archive.sprites.contains(name) || self.base_sprites.contains(name)
+	}
+
+	pub fn has_script(&self, name: &str) -> bool {
+		self.archive.scripts.contains(name) || self.base_scripts.contains(name)
+	}
+}
+
+pub type ArchiveLintRule = fn(&ArchiveLintContext) -> Vec<Lint>;
+
+pub const ARCHIVE_LINT_RULES: [ArchiveLintRule; 6] = [
+	lint_missing_song_name_en,
+	lint_missing_levels,
+	lint_missing_charts,
+	lint_missing_module_objsets,
+	lint_missing_cstm_item_sprites,
+	lint_duplicate_ids,
+];
+
+pub async fn lint_archive(archive: ArchiveContents, state: &AppState) -> Vec<Lint> {
+	let base_objsets = load_db_entries("objsets", Some(-1), state)
+		.await
+		.unwrap_or_default()
+		.into_iter()
+		.map(|entry| entry.name)
+		.collect();
+	let base_sprites = load_db_entries("sprites", Some(-1), state)
+		.await
+		.unwrap_or_default()
+		.into_iter()
+		.map(|entry| entry.name)
+		.collect();
+	let base_scripts = load_pvs(Some(-1), state)
+		.await
+		.unwrap_or_default()
+		.into_iter()
+		.flat_map(|pv| pv.charts)
+		.flatten()
+		.filter(|chart| chart.found)
+		.map(|chart| chart.script_file_name)
+		.collect();
+
+	let context = ArchiveLintContext {
+		archive,
+		base_objsets,
+		base_sprites,
+		base_scripts,
+	};
+
+	ARCHIVE_LINT_RULES
+		.iter()
+		.flat_map(|rule| rule(&context))
+		.collect()
+}
+
+fn lint_missing_song_name_en(context: &ArchiveLintContext) -> Vec<Lint> {
+	context
+		.archive
+		.pv_dbs
+		.iter()
+		.filter_map(|pv_db| {
+			let ids = pv_db
+				.data
+				.pvs
+				.iter()
+				.filter(|(_, pv)| pv.song_name_en.trim().is_empty())
+				.map(|(id, _)| *id as i64)
+				.collect::<Vec<_>>();
+			if ids.is_empty() {
+				return None;
+			}
+
+			Some(Lint {
+				rule: String::from("missing_song_name_en"),
+				severity: LintSeverity::Warning,
+				ids,
+				explanation: format!(
+					"PVs in {} have no song_name_en and will show the Japanese name in English",
+					pv_db.name()
+				),
+			})
+		})
+		.collect()
+}
+
+fn lint_missing_levels(context: &ArchiveLintContext) -> Vec<Lint> {
+	context
+		.archive
+		.pv_dbs
+		.iter()
+		.filter_map(|pv_db| {
+			let ids = pv_db
+				.data
+				.pvs
+				.iter()
+				.filter(|(_, pv)| {
+					let Some(difficulties) = &pv.difficulty else {
+						return false;
+					};
+					[
+						&difficulties.easy,
+						&difficulties.normal,
+						&difficulties.hard,
+						&difficulties.extreme,
+					]
+					.into_iter()
+					.flatten()
+					.flatten()
+					.any(|difficulty| difficulty.level.is_none())
+				})
+				.map(|(id, _)| *id as i64)
+				.collect::<Vec<_>>();
+			if ids.is_empty() {
+				return None;
+			}
+
+			Some(Lint {
+				rule: String::from("missing_levels"),
+				severity: LintSeverity::Warning,
+				ids,
+				explanation: format!(
+					"PVs in {} have difficulties without a level set",
+					pv_db.name()
+				),
+			})
+		})
+		.collect()
+}
+
+fn lint_missing_charts(context: &ArchiveLintContext) -> Vec<Lint> {
+	context
+		.archive
+		.pv_dbs
+		.iter()
+		.filter_map(|pv_db| {
+			let ids = pv_db
+				.data
+				.pvs
+				.iter()
+				.filter(|(_, pv)| {
+					let Some(difficulties) = &pv.difficulty else {
+						return false;
+					};
+					[
+						&difficulties.easy,
+						&difficulties.normal,
+						&difficulties.hard,
+						&difficulties.extreme,
+					]
+					.into_iter()
+					.flatten()
+					.flatten()
+					.filter_map(|difficulty| difficulty.script_file_name.as_ref())
+					.any(|script| !context.has_script(script))
+				})
+				.map(|(id, _)| *id as i64)
+				.collect::<Vec<_>>();
+			if ids.is_empty() {
+				return None;
+			}
+
+			Some(Lint {
+				rule: String::from("missing_charts"),
+				severity: LintSeverity::Warning,
+				ids,
+				explanation: format!(
+					"PVs in {} reference chart scripts that are not in the archive or the base game",
+					pv_db.name()
+				),
+			})
+		})
+		.collect()
+}
+
+fn lint_missing_module_objsets(context: &ArchiveLintContext) -> Vec<Lint> {
+	let mut missing = BTreeMap::new();
+	for module_db in &context.archive.module_dbs {
+		for (id, module) in &module_db.data.modules {
+			for item in &module.cos.items {
+				for objset in &item.objset {
+					if !context.has_objset(objset) {
+						missing
+							.entry(objset.clone())
+							.or_insert_with(BTreeSet::new)
+							.insert(*id as i64);
+					}
+				}
+			}
+		}
+	}
+
+	missing
+		.into_iter()
+		.map(|(objset, ids)| Lint {
+			rule: String::from("missing_module_objsets"),
+			severity: LintSeverity::Warning,
+			ids: ids.into_iter().collect(),
+			explanation: format!(
+				"Modules use the objset {objset} which is not in the archive or the base game"
+			),
+		})
+		.collect()
+}
+
+fn lint_missing_cstm_item_sprites(context: &ArchiveLintContext) -> Vec<Lint> {
+	let ids = context
+		.archive
+		.module_dbs
+		.iter()
+		.flat_map(|module_db| module_db.data.cstm_items.keys())
+		.filter(|id| !context.has_sprite(&format!("SPR_CMNITM_THMB{:03}_ITM_IMG", id)))
+		.map(|id| *id as i64)
+		.unique()
+		.collect::<Vec<_>>();
+	if ids.is_empty() {
+		return Vec::new();
+	}
+
+	vec![Lint {
+		rule: String::from("missing_cstm_item_sprites"),
+		severity: LintSeverity::Warning,
+		ids,
+		explanation: String::from(
+			"Customize items point at thumbnail sprites that are not in the archive or the base game",
+		),
+	}]
+}
+
+fn duplicate_ids<T>(
+	rule: &str,
+	kind: &str,
+	files: &[ArchiveFile<T>],
+	ids: impl Fn(&T) -> Vec<i64>,
+) -> Vec<Lint> {
+	// Every rom folder and included mod is loaded together, so ids are compared across all of them
+	let mut seen: BTreeMap<(&str, i64), Vec<&ArchiveFile<T>>> = BTreeMap::new();
+	for file in files {
+		for id in ids(&file.data) {
+			seen.entry((file.file, id)).or_default().push(file);
+		}
+	}
+
+	let mut duplicates: BTreeMap<Vec<String>, Vec<i64>> = BTreeMap::new();
+	for ((_, id), files) in seen {
+		let files = files
+			.into_iter()
+			.map(|file| (file.key(), file))
+			.unique_by(|(key, _)| *key)
+			.map(|(_, file)| file.name())
+			.collect::<Vec<_>>();
+		if files.len() > 1 {
+			duplicates.entry(files).or_default().push(id);
+		}
+	}
+
+	duplicates
+		.into_iter()
+		.map(|(files, ids)| Lint {
+			rule: String::from(rule),
+			severity: LintSeverity::Warning,
+			ids,
+			explanation: format!(
+				"{kind} ids are defined more than once in {}, only one of them will be used",
+				files.join(", ")
+			),
+		})
+		.collect()
+}
+
+fn lint_duplicate_ids(context: &ArchiveLintContext) -> Vec<Lint> {
+	let mut lints = duplicate_ids("duplicate_pv_ids", "PV", &context.archive.pv_dbs, |pv_db| {
+		pv_db.pvs.keys().map(|id| *id as i64).collect()
+	});
+	lints.extend(duplicate_ids(
+		"duplicate_module_ids",
+		"Module",
+		&context.archive.module_dbs,
+		|module_db| module_db.modules.keys().map(|id| *id as i64).collect(),
+	));
+	lints.extend(duplicate_ids(
+		"duplicate_cstm_item_ids",
+		"Customize item",
+		&context.archive.module_dbs,
+		|module_db| module_db.cstm_items.keys().map(|id| *id as i64).collect(),
+	));
+	lints.extend(duplicate_ids(
+		"duplicate_db_ids",
+		"Database entry",
+		&context.archive.db_entries,
+		|entries| entries.keys().map(|id| *id as i64).collect(),
+	));
+	lints
+}
+
+pub async fn store_archive_lints(post_id: i32, lints: &[Lint], state: &AppState) {
+	let now = time::OffsetDateTime::now_utc();
+	let time = time::PrimitiveDateTime::new(now.date(), now.time());
+
+	_ = sqlx::query!(
+		"INSERT INTO post_lint_reports (post_id, lints, archive_lints, time) VALUES ($1, '[]', $2, $3) ON CONFLICT (post_id) DO UPDATE SET archive_lints = EXCLUDED.archive_lints",
+		post_id,
+		sqlx::types::Json(lints) as _,
+		time
+	)
+	.execute(&state.db)
+	.await;
+}
+
+pub async fn get_lint_report(post_id: i32, state: &AppState) -> Option<LintReport> {
+	let report = sqlx::query!(
+		r#"SELECT post_id, lints as "lints: sqlx::types::Json<Vec<Lint>>", archive_lints as "archive_lints: sqlx::types::Json<Vec<Lint>>", admin_override, time FROM post_lint_reports WHERE post_id = $1"#,
+		post_id
+	)
+	.fetch_one(&state.db)
+	.await
+	.ok()?;
+
+	let mut lints = report.archive_lints.0;
+	lints.extend(report.lints.0);
+	lints.sort_by(|a, b| b.severity.cmp(&a.severity));
+
+	Some(LintReport {
+		post_id: report.post_id,
+		lints,
+		admin_override: report.admin_override,
+		time: report.time.assume_offset(time::UtcOffset::UTC),
+	})
+}
+
+/// Re-lints the post, an admin override is kept until `clear_admin_override` is called for new files
+pub async fn update_lint_report(post_id: i32, state: &AppState) -> Option<LintReport> {
+	let post = Post::get_full(post_id, &state.db).await?;
+	let detail = build_post_detail(post, state).await;
+	let lints = lint_post_detail(&detail);
+
+	let now = time::OffsetDateTime::now_utc();
+	let time = time::PrimitiveDateTime::new(now.date(), now.time());
+
+	let report = sqlx::query!(
+		r#"INSERT INTO post_lint_reports (post_id, lints, admin_override, time) VALUES ($1, $2, false, $3) ON CONFLICT (post_id) DO UPDATE SET lints = EXCLUDED.lints, time = EXCLUDED.time RETURNING archive_lints as "archive_lints: sqlx::types::Json<Vec<Lint>>", admin_override"#,
+		post_id,
+		sqlx::types::Json(&lints) as _,
+		time
+	)
+	.fetch_one(&state.db)
+	.await
+	.ok()?;
+
+	let mut lints = [report.archive_lints.0, lints].concat();
+	lints.sort_by(|a, b| b.severity.cmp(&a.severity));
+
+	Some(LintReport {
+		post_id,
+		lints,
+		admin_override: report.admin_override,
+		time: now,
+	})
+}
+
+/// An override only applies to the files an admin looked at, so it's dropped when the files change
+pub async fn clear_admin_override(post_id: i32, state: &AppState) {
+	_ = sqlx::query!(
+		"UPDATE post_lint_reports SET admin_override = false WHERE post_id = $1",
+		post_id
+	)
+	.execute(&state.db)
+	.await;
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/posts/{id}/lint",
+	params(
+		("id" = i32, Path)
+	),
+	responses(
+		(status = 200, body = LintReport, content_type = "application/json"),
+		(status = 401),
+		(status = 404)
+	)
+)]
+pub async fn lint_report(
+	Path(id): Path<i32>,
+	user: Result<User, ErrorTemplate>,
+	State(state): State<AppState>,
+) -> Result<Json<LintReport>, StatusCode> {
+	let Some(post) = Post::get_short(id, &state.db).await else {
+		return Err(StatusCode::NOT_FOUND);
+	};
+
+	if post.private {
+		let Ok(user) = user else {
+			return Err(StatusCode::UNAUTHORIZED);
+		};
+		if !post.authors.contains(&user) && !user.is_admin(&state.config) {
+			return Err(StatusCode::UNAUTHORIZED);
+		}
+	}
+
+	if let Some(report) = get_lint_report(id, &state).await {
+		return Ok(Json(report));
+	}
+
+	update_lint_report(id, &state)
+		.await
+		.map(Json)
+		.ok_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Lets an admin allow a post to be made public regardless of the errors in its lint report
+#[utoipa::path(
+	post,
+	path = "/api/v1/posts/{id}/lint/override",
+	params(
+		("id" = i32, Path)
+	),
+	request_body = bool,
+	responses(
+		(status = 200),
+		(status = 401),
+		(status = 404)
+	)
+)]
+pub async fn override_lint_report(
+	Path(id): Path<i32>,
+	user: User,
+	State(state): State<AppState>,
+	Json(admin_override): Json<bool>,
+) -> StatusCode {
+	if !user.is_admin(&state.config) {
+		return StatusCode::UNAUTHORIZED;
+	}
+
+	if get_lint_report(id, &state).await.is_none() && update_lint_report(id, &state).await.is_none()
+	{
+		return StatusCode::NOT_FOUND;
+	}
+
+	match sqlx::query!(
+		"UPDATE post_lint_reports SET admin_override = $2 WHERE post_id = $1",
+		id,
+		admin_override
+	)
+	.execute(&state.db)
+	.await
+	{
+		Ok(_) => StatusCode::OK,
+		Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+	}
+}
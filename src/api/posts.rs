@@ -232,6 +232,15 @@ pub async fn edit_post(
 	if !data.private && post.files.is_empty() {
 		return Err(StatusCode::BAD_REQUEST);
 	}
+	if post.private && !data.private && !user.is_admin(&state.config) {
+		// Reservations may have changed since the report was made, an admin override is kept by the update
+		let Some(report) = crate::api::lint::update_lint_report(id, &state).await else {
+			return Err(StatusCode::SERVICE_UNAVAILABLE);
+		};
+		if report.blocks_publishing() {
+			return Err(StatusCode::FORBIDDEN);
+		}
+	}
 
 	let explicit_reason = if !data.explicit || data.explicit_reason.is_empty() {
 		None
@@ -520,51 +529,7 @@ pub async fn continue_pending_upload_ws(mut socket: ws::WebSocket, state: AppSta
 		}
 	}
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("pvs"))
-		.with_filter(&format!("post={}", post.id))
-		.execute::<MeilisearchPv>()
-		.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("modules"))
-		.with_filter(&format!("post_id={}", post.id))
-		.execute::<MeilisearchModule>()
-		.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("cstm_items"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchCstmItem>()
-	.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("nc_songs"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchNcSong>()
-	.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("sprites"))
-		.with_filter(&format!("post_id={}", post.id))
-		.execute::<MeilisearchDbEntry>()
-		.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("aets"))
-		.with_filter(&format!("post_id={}", post.id))
-		.execute::<MeilisearchDbEntry>()
-		.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("objsets"))
-		.with_filter(&format!("post_id={}", post.id))
-		.execute::<MeilisearchDbEntry>()
-		.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("textures"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchDbEntry>()
-	.await;
+	delete_extracted_data(post.id, &state).await;
 
 	let mut pending_exists = false;
 	for file in &pending_upload.files {
@@ -649,6 +614,9 @@ pub async fn continue_pending_upload_ws(mut socket: ws::WebSocket, state: AppSta
 	let now = time::OffsetDateTime::now_utc();
 	let time = time::PrimitiveDateTime::new(now.date(), now.time());
 
+	// Posts stay private until the lint report of the new files says they can be published
+	let publish_after_lint = !data.private && !user.is_admin(&state.config);
+
 	_ = sqlx::query!(
 		"UPDATE posts SET files = $2, local_files = $3, time = $4, name = $5, text = $6, type = $7, private = $8, explicit = $9, explicit_reason = $10, filesizes = $11 WHERE id = $1",
 		post.id,
@@ -658,7 +626,7 @@ pub async fn continue_pending_upload_ws(mut socket: ws::WebSocket, state: AppSta
 		data.name,
 		data.text,
 		data.post_type,
-		data.private,
+		data.private || publish_after_lint,
 		data.explicit,
 		explicit_reason,
 		&pending_upload.completed,
@@ -666,6 +634,18 @@ pub async fn continue_pending_upload_ws(mut socket: ws::WebSocket, state: AppSta
 	.execute(&state.db)
 	.await;
 
+	for (file, filesize) in pending_upload.files.iter().zip(&pending_upload.completed) {
+		_ = sqlx::query!(
+			"INSERT INTO post_file_history (post_id, file_name, filesize, time) VALUES ($1, $2, $3, $4)",
+			post.id,
+			file,
+			filesize,
+			time
+		)
+		.execute(&state.db)
+		.await;
+	}
+
 	_ = sqlx::query!("DELETE FROM pending_uploads WHERE user_id = $1", user.id)
 		.execute(&state.db)
 		.await;
@@ -678,7 +658,110 @@ pub async fn continue_pending_upload_ws(mut socket: ws::WebSocket, state: AppSta
 			.await;
 	};
 
+	update_file_hashes(post.id, &state).await;
+	crate::api::lint::clear_admin_override(post.id, &state).await;
+
 	_ = tokio::spawn(extract_post_data(post.id, state.clone())).await;
+
+	if !publish_after_lint {
+		return;
+	}
+	let report = match crate::api::lint::get_lint_report(post.id, &state).await {
+		Some(report) => Some(report),
+		None => crate::api::lint::update_lint_report(post.id, &state).await,
+	};
+	if report.map_or(true, |report| report.blocks_publishing()) {
+		_ = socket
+			.send(ws::Message::Text(ws::Utf8Bytes::from_static(
+				"{\"error\": \"The post was kept private, check its lint report on the edit page\"}",
+			)))
+			.await;
+		return;
+	}
+
+	_ = sqlx::query!("UPDATE posts SET private = false WHERE id = $1", post.id)
+		.execute(&state.db)
+		.await;
+
+	if let Some(post) = Post::get_short(post.id, &state.db).await {
+		_ = state
+			.meilisearch
+			.index("posts")
+			.add_or_update(&[post], None)
+			.await;
+	};
+}
+
+pub async fn hash_file(path: String) -> Option<String> {
+	use sha2::Digest;
+
+	tokio::task::spawn_blocking(move || {
+		let mut file = std::fs::File::open(path).ok()?;
+		let mut hasher = sha2::Sha256::new();
+		std::io::copy(&mut file, &mut hasher).ok()?;
+		Some(format!("{:x}", hasher.finalize()))
+	})
+	.await
+	.ok()?
+}
+
+pub async fn update_file_hashes(post_id: i32, state: &AppState) {
+	let Ok(post) = sqlx::query!("SELECT local_files FROM posts WHERE id = $1", post_id)
+		.fetch_one(&state.db)
+		.await
+	else {
+		return;
+	};
+
+	let mut hashes = Vec::with_capacity(post.local_files.len());
+	for file in &post.local_files {
+		hashes.push(hash_file(format!("{}/{file}", state.config.storage_path)).await);
+	}
+
+	// Files that can't be hashed are stored as an empty string so the post isn't retried forever
+	_ = sqlx::query!(
+		"UPDATE posts SET file_hashes = $2 WHERE id = $1",
+		post_id,
+		&hashes
+			.iter()
+			.map(|hash| hash.clone().unwrap_or_default())
+			.collect::<Vec<_>>()
+	)
+	.execute(&state.db)
+	.await;
+
+	// The latest upload of each file, rows backfilled by the migration may not share the posts time
+	for (file, hash) in post.local_files.iter().zip(&hashes) {
+		let Some(hash) = hash else {
+			continue;
+		};
+		let file_name = file.split('/').last().unwrap_or_default();
+		_ = sqlx::query!(
+			r#"
+			UPDATE post_file_history SET file_hash = $3
+			WHERE post_id = $1 AND file_name = $2 AND time = (
+				SELECT MAX(time) FROM post_file_history WHERE post_id = $1 AND file_name = $2
+			)
+			"#,
+			post_id,
+			file_name,
+			hash
+		)
+		.execute(&state.db)
+		.await;
+	}
+}
+
+pub async fn update_missing_file_hashes(state: AppState) {
+	for post in sqlx::query!(
+		"SELECT id FROM posts WHERE cardinality(file_hashes) != cardinality(local_files)"
+	)
+	.fetch_all(&state.db)
+	.await
+	.unwrap_or_default()
+	{
+		update_file_hashes(post.id, &state).await;
+	}
 }
 
 #[derive(Serialize, Deserialize)]
@@ -769,14 +852,16 @@ pub async fn download(
 	Path((id, variant)): Path<(i32, i32)>,
 	State(state): State<AppState>,
 	user: Result<User, ErrorTemplate>,
+	headers: axum::http::HeaderMap,
+	ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
 ) -> Result<Redirect, StatusCode> {
 	let Some(post) = Post::get_short(id, &state.db).await else {
 		return Err(StatusCode::NOT_FOUND);
 	};
 
 	if post.private {
-		if let Ok(user) = user {
-			if !post.authors.contains(&user) && !state.config.admins.contains(&user.id) {
+		if let Ok(user) = &user {
+			if !post.authors.contains(user) && !state.config.admins.contains(&user.id) {
 				return Err(StatusCode::UNAUTHORIZED);
 			}
 		} else {
@@ -784,25 +869,33 @@ pub async fn download(
 		}
 	}
 
-	_ = sqlx::query!(
-		"UPDATE posts SET download_count = download_count +1 WHERE id = $1",
-		id
-	)
-	.execute(&state.db)
-	.await;
-
-	if let Some(post) = Post::get_short(id, &state.db).await {
-		_ = state
-			.meilisearch
-			.index("posts")
-			.add_or_update(&[post], None)
-			.await;
-	};
-
 	let Some(file) = post.files.get(variant as usize) else {
 		return Err(StatusCode::BAD_REQUEST);
 	};
 
+	let client = crate::api::downloads::client_id(user.as_ref().ok(), &headers, addr, &state);
+	let counted = match &client {
+		Some(client) => crate::api::downloads::record_download(id, variant, client, &state).await,
+		None => false,
+	};
+
+	if counted {
+		_ = sqlx::query!(
+			"UPDATE posts SET download_count = download_count +1 WHERE id = $1",
+			id
+		)
+		.execute(&state.db)
+		.await;
+
+		if let Some(post) = Post::get_short(id, &state.db).await {
+			_ = state
+				.meilisearch
+				.index("posts")
+				.add_or_update(&[post], None)
+				.await;
+		};
+	}
+
 	Ok(Redirect::to(file))
 }
 
@@ -821,6 +914,372 @@ pub async fn download_head(
 	Ok(Redirect::to(file))
 }
 
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct InstalledFile {
+	pub post_id: i32,
+	/// Either the file name or the sha256 hash of the installed archive
+	pub file: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, PartialEq)]
+pub enum UpdateStatus {
+	Current,
+	Outdated,
+	Unknown,
+	NotFound,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct UpdateFile {
+	pub name: String,
+	pub size: Option<i64>,
+	pub hash: Option<String>,
+	pub url: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct UpdateCheck {
+	pub post_id: i32,
+	pub file: String,
+	pub status: UpdateStatus,
+	/// When the installed file was uploaded, if it is a known upload of this post
+	#[serde(with = "time::serde::rfc3339::option")]
+	pub installed_time: Option<time::OffsetDateTime>,
+	#[serde(with = "time::serde::rfc3339::option")]
+	pub latest_time: Option<time::OffsetDateTime>,
+	pub files: Vec<UpdateFile>,
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/posts/updates",
+	request_body = Vec<InstalledFile>,
+	responses(
+		(status = 200, body = Vec<UpdateCheck>, content_type = "application/json"),
+		(status = 400, body = String)
+	)
+)]
+pub async fn check_updates(
+	State(state): State<AppState>,
+	Json(installed): Json<Vec<InstalledFile>>,
+) -> Result<Json<Vec<UpdateCheck>>, (StatusCode, String)> {
+	if installed.len() > 1000 {
+		return Err((
+			StatusCode::BAD_REQUEST,
+			String::from("Too many files, at most 1000 may be checked at once"),
+		));
+	}
+
+	let ids = installed
+		.iter()
+		.map(|installed| installed.post_id)
+		.unique()
+		.collect::<Vec<_>>();
+
+	let posts = sqlx::query!(
+		"SELECT id, time, private, local_files, filesizes, file_hashes FROM posts WHERE id = ANY($1)",
+		&ids
+	)
+	.fetch_all(&state.db)
+	.await
+	.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+	.into_iter()
+	.map(|post| (post.id, post))
+	.collect::<BTreeMap<_, _>>();
+
+	let mut history: BTreeMap<i32, Vec<_>> = BTreeMap::new();
+	for upload in sqlx::query!(
+		"SELECT post_id, file_name, file_hash, time FROM post_file_history WHERE post_id = ANY($1)",
+		&ids
+	)
+	.fetch_all(&state.db)
+	.await
+	.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+	{
+		history.entry(upload.post_id).or_default().push(upload);
+	}
+
+	let mut checks = Vec::with_capacity(installed.len());
+	for installed in installed {
+		let Some(post) = posts.get(&installed.post_id).filter(|post| !post.private) else {
+			checks.push(UpdateCheck {
+				post_id: installed.post_id,
+				file: installed.file,
+				status: UpdateStatus::NotFound,
+				installed_time: None,
+				latest_time: None,
+				files: Vec::new(),
+			});
+			continue;
+		};
+
+		let latest_time = post.time.assume_offset(time::UtcOffset::UTC);
+		let files = post
+			.local_files
+			.iter()
+			.enumerate()
+			.map(|(i, file)| UpdateFile {
+				name: file
+					.split("/")
+					.last()
+					.map(|s| String::from(s))
+					.unwrap_or(String::new()),
+				size: post.filesizes.get(i).cloned(),
+				hash: post
+					.file_hashes
+					.get(i)
+					.filter(|hash| !hash.is_empty())
+					.cloned(),
+				url: format!(
+					"https://divamodarchive.com/api/v1/posts/{}/download/{i}",
+					post.id
+				),
+			})
+			.collect::<Vec<_>>();
+
+		let hash_matches = |hash: Option<&String>| {
+			hash.is_some_and(|hash| hash.eq_ignore_ascii_case(&installed.file))
+		};
+		let uploads = history
+			.get(&post.id)
+			.map_or(&[][..], |uploads| uploads.as_slice());
+
+		let (status, installed_time) = if files.iter().any(|file| hash_matches(file.hash.as_ref()))
+		{
+			(UpdateStatus::Current, Some(latest_time))
+		} else if let Some(upload) = uploads
+			.iter()
+			.filter(|upload| hash_matches(upload.file_hash.as_ref()))
+			.max_by_key(|upload| upload.time)
+		{
+			(
+				UpdateStatus::Outdated,
+				Some(upload.time.assume_offset(time::UtcOffset::UTC)),
+			)
+		} else {
+			// Authors often reuse file names, so a name is only trusted if a single upload ever used it
+			let times = uploads
+				.iter()
+				.filter(|upload| upload.file_name == installed.file)
+				.map(|upload| upload.time)
+				.unique()
+				.collect::<Vec<_>>();
+			match times.as_slice() {
+				[upload_time] if *upload_time == post.time => {
+					(UpdateStatus::Current, Some(latest_time))
+				}
+				[upload_time] => (
+					UpdateStatus::Outdated,
+					Some(upload_time.assume_offset(time::UtcOffset::UTC)),
+				),
+				_ => (UpdateStatus::Unknown, None),
+			}
+		};
+
+		checks.push(UpdateCheck {
+			post_id: installed.post_id,
+			file: installed.file,
+			status,
+			installed_time,
+			latest_time: Some(latest_time),
+			files,
+		});
+	}
+
+	Ok(Json(checks))
+}
+
+const MAX_IDENTIFIED: usize = 20;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct IdentifyArchive {
+	/// sha256 hashes of mod archives
+	#[serde(default)]
+	pub hashes: Vec<String>,
+	/// The name field of the mods config.toml
+	pub name: Option<String>,
+	#[serde(default)]
+	pub pv_ids: Vec<i32>,
+	#[serde(default)]
+	pub module_ids: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct IdentifiedPost {
+	pub post: Post,
+	pub hashes: Vec<String>,
+	pub name: bool,
+	pub pv_ids: Vec<i32>,
+	pub module_ids: Vec<i32>,
+}
+
+/// Finds the posts an installed mod came from, at most 20 are returned with the best matches first
+#[utoipa::path(
+	post,
+	path = "/api/v1/posts/identify",
+	request_body = IdentifyArchive,
+	responses(
+		(status = 200, body = Vec<IdentifiedPost>, content_type = "application/json"),
+		(status = 400, body = String)
+	)
+)]
+pub async fn identify_archive(
+	State(state): State<AppState>,
+	Json(archive): Json<IdentifyArchive>,
+) -> Result<Json<Vec<IdentifiedPost>>, (StatusCode, String)> {
+	if archive.hashes.len() + archive.pv_ids.len() + archive.module_ids.len() > 1000 {
+		return Err((
+			StatusCode::BAD_REQUEST,
+			String::from("Too many hashes or ids, at most 1000 may be checked at once"),
+		));
+	}
+
+	let mut hashes: BTreeMap<i32, Vec<String>> = BTreeMap::new();
+	let mut names = BTreeSet::new();
+	let mut pv_ids: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+	let mut module_ids: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+
+	if !archive.hashes.is_empty() {
+		let wanted = archive
+			.hashes
+			.iter()
+			.map(|hash| hash.trim().to_lowercase())
+			.collect::<Vec<_>>();
+		for upload in sqlx::query!(
+			"SELECT DISTINCT post_id, file_hash FROM post_file_history WHERE file_hash = ANY($1)",
+			&wanted
+		)
+		.fetch_all(&state.db)
+		.await
+		.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+		{
+			if let Some(hash) = upload.file_hash {
+				hashes.entry(upload.post_id).or_default().push(hash);
+			}
+		}
+	}
+
+	if let Some(name) = &archive.name {
+		for post in sqlx::query!(
+			"SELECT DISTINCT post_id FROM post_mod_names WHERE lower(name) = lower($1)",
+			name.trim()
+		)
+		.fetch_all(&state.db)
+		.await
+		.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+		{
+			names.insert(post.post_id);
+		}
+	}
+
+	if !archive.pv_ids.is_empty() {
+		let filter = archive
+			.pv_ids
+			.iter()
+			.map(|id| format!("pv_id={id}"))
+			.intersperse(String::from(" OR "))
+			.collect::<String>();
+		let pvs = meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("pvs"))
+			.with_filter(&format!("({filter}) AND post!=-1"))
+			.with_limit(u32::MAX as usize)
+			.execute::<MeilisearchPv>()
+			.await
+			.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+		for pv in pvs.results {
+			pv_ids.entry(pv.post).or_default().push(pv.pv_id);
+		}
+	}
+
+	if !archive.module_ids.is_empty() {
+		let filter = archive
+			.module_ids
+			.iter()
+			.map(|id| format!("module_id={id}"))
+			.intersperse(String::from(" OR "))
+			.collect::<String>();
+		let modules =
+			meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("modules"))
+				.with_filter(&format!("({filter}) AND post_id!=-1"))
+				.with_limit(u32::MAX as usize)
+				.execute::<MeilisearchModule>()
+				.await
+				.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+		for module in modules.results {
+			module_ids
+				.entry(module.post_id)
+				.or_default()
+				.push(module.module_id);
+		}
+	}
+
+	let candidates = hashes
+		.keys()
+		.chain(names.iter())
+		.chain(pv_ids.keys())
+		.chain(module_ids.keys())
+		.cloned()
+		.collect::<BTreeSet<_>>()
+		.into_iter()
+		.collect::<Vec<_>>();
+
+	for ids in pv_ids.values_mut().chain(module_ids.values_mut()) {
+		ids.sort();
+		ids.dedup();
+	}
+
+	let mut candidates = sqlx::query!(
+		"SELECT id FROM posts WHERE id = ANY($1) AND NOT private",
+		&candidates
+	)
+	.fetch_all(&state.db)
+	.await
+	.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+	.into_iter()
+	.map(|post| post.id)
+	.collect::<Vec<_>>();
+
+	// Exact archive matches first, then config.toml name matches, then by how many ids overlap
+	candidates.sort_by_key(|id| {
+		std::cmp::Reverse((
+			hashes.contains_key(id),
+			names.contains(id),
+			pv_ids.get(id).map_or(0, |ids| ids.len())
+				+ module_ids.get(id).map_or(0, |ids| ids.len()),
+			*id,
+		))
+	});
+	candidates.truncate(MAX_IDENTIFIED);
+
+	let mut identified = Vec::new();
+	for mut post in Post::get_many(&candidates, &state.db).await {
+		let id = post.id;
+		for i in 0..post.files.len() {
+			post.files[i] = format!(
+				"https://divamodarchive.com/api/v1/posts/{}/download/{i}",
+				post.id
+			);
+			post.local_files[i] = post.local_files[i]
+				.split("/")
+				.last()
+				.map(|s| String::from(s))
+				.unwrap_or(String::new());
+		}
+
+		let pv_ids = pv_ids.remove(&id).unwrap_or_default();
+		let module_ids = module_ids.remove(&id).unwrap_or_default();
+
+		identified.push(IdentifiedPost {
+			post,
+			hashes: hashes.remove(&id).unwrap_or_default(),
+			name: names.contains(&id),
+			pv_ids,
+			module_ids,
+		});
+	}
+
+	Ok(Json(identified))
+}
+
 pub async fn like(Path(id): Path<i32>, user: User, State(state): State<AppState>) -> StatusCode {
 	let Some(post) = Post::get_short(id, &state.db).await else {
 		return StatusCode::NOT_FOUND;
@@ -923,6 +1382,7 @@ pub async fn get_multiple_posts(
 		query: None,
 		sort: None,
 		filter: Some(filter),
+		author: None,
 		limit: Some(posts.post_id.len()),
 		offset: None,
 	};
@@ -932,18 +1392,34 @@ pub async fn get_multiple_posts(
 #[derive(Serialize, Deserialize, Clone, IntoParams)]
 pub struct SearchParams {
 	pub query: Option<String>,
-	/// One of time:desc, time:asc, like_count:desc, download_count:desc
+	/// One of time:desc, time:asc, like_count:desc, download_count:desc, trending:desc
 	pub sort: Option<String>,
 	/**
 	A meilisearch filter, such as `post_type = Plugin AND id != 100`
-	Attributes: post_type, id
-	post_type values are shown in the PostType schema, id is an i32
+	Attributes: post_type, id, tags.name, tags.category, authors.id, authors.name, explicit
+	post_type values are shown in the PostType schema, tags.category values in the TagCategory schema, id is an i32
 	*/
 	pub filter: Option<String>,
+	/// Only return posts by the user with this id
+	pub author: Option<i64>,
 	pub limit: Option<usize>,
 	pub offset: Option<usize>,
 }
 
+impl SearchParams {
+	fn meilisearch_filter(&self) -> String {
+		let mut filter = if let Some(filter) = &self.filter {
+			format!("({filter}) AND private=false")
+		} else {
+			String::from("private=false")
+		};
+		if let Some(author) = self.author {
+			filter.push_str(&format!(" AND authors.id={author}"));
+		}
+		filter
+	}
+}
+
 #[utoipa::path(
 	get,
 	path = "/api/v1/posts",
@@ -964,11 +1440,7 @@ pub async fn search_posts(
 
 	search.query = query.query.as_ref().map(|query| query.as_str());
 
-	let filter = if let Some(filter) = &query.filter {
-		format!("({filter}) AND private=false")
-	} else {
-		String::from("private=false")
-	};
+	let filter = query.meilisearch_filter();
 
 	search.filter = Some(meilisearch_sdk::search::Filter::new(sqlx::Either::Left(
 		filter.as_str(),
@@ -990,33 +1462,38 @@ pub async fn search_posts(
 		.await
 		.map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
 
-	let posts = posts
-		.hits
-		.into_iter()
-		.map(|p| p.result.id)
-		.collect::<Vec<_>>();
+	Ok(Json(load_search_hits(posts.hits, &state).await))
+}
+
+// Loads the full posts for search hits, removing any that no longer exist from the index
+async fn load_search_hits(
+	hits: Vec<meilisearch_sdk::search::SearchResult<Post>>,
+	state: &AppState,
+) -> Vec<Post> {
+	let posts = hits.into_iter().map(|p| p.result.id).collect::<Vec<_>>();
 
-	let mut vec = Vec::with_capacity(posts.len());
+	let mut vec = Post::get_many(&posts, &state.db).await;
 	for id in posts {
-		if let Some(mut post) = Post::get_full(id, &state.db).await {
-			for i in 0..post.files.len() {
-				post.files[i] = format!(
-					"https://divamodarchive.com/api/v1/posts/{}/download/{i}",
-					post.id
-				);
-				post.local_files[i] = post.local_files[i]
-					.split("/")
-					.last()
-					.map(|s| String::from(s))
-					.unwrap_or(String::new());
-			}
-			vec.push(post);
-		} else {
-			_ = index.delete_document(id).await;
+		if !vec.iter().any(|post| post.id == id) {
+			_ = state.meilisearch.index("posts").delete_document(id).await;
+		}
+	}
+
+	for post in &mut vec {
+		for i in 0..post.files.len() {
+			post.files[i] = format!(
+				"https://divamodarchive.com/api/v1/posts/{}/download/{i}",
+				post.id
+			);
+			post.local_files[i] = post.local_files[i]
+				.split("/")
+				.last()
+				.map(|s| String::from(s))
+				.unwrap_or(String::new());
 		}
 	}
 
-	Ok(Json(vec))
+	vec
 }
 
 #[utoipa::path(
@@ -1039,11 +1516,7 @@ pub async fn count_posts(
 
 	search.query = query.query.as_ref().map(|query| query.as_str());
 
-	let filter = if let Some(filter) = &query.filter {
-		format!("({filter}) AND private=false")
-	} else {
-		String::from("private=false")
-	};
+	let filter = query.meilisearch_filter();
 
 	search.filter = Some(meilisearch_sdk::search::Filter::new(sqlx::Either::Left(
 		filter.as_str(),
@@ -1068,6 +1541,72 @@ pub async fn count_posts(
 	Ok(Json(posts.estimated_total_hits.unwrap_or(0)))
 }
 
+const POST_FACETS: [&str; 4] = ["post_type", "tags.name", "authors.name", "explicit"];
+/// Facet values are sorted by how many posts have them, only this many of the most common are returned
+pub const MAX_FACET_VALUES: usize = 20;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct FacetedPosts {
+	pub posts: Vec<Post>,
+	pub total: usize,
+	/// Matching post counts for the 20 most common values of post_type, tags.name, authors.name and explicit
+	pub facets: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/posts/facets",
+	params(
+		SearchParams
+	),
+	responses(
+		(status = 200, body = FacetedPosts, content_type = "application/json"),
+		(status = 400, body = String)
+	)
+)]
+pub async fn search_posts_faceted(
+	Query(query): Query<SearchParams>,
+	State(state): State<AppState>,
+) -> Result<Json<FacetedPosts>, (StatusCode, String)> {
+	let index = state.meilisearch.index("posts");
+	let mut search = meilisearch_sdk::search::SearchQuery::new(&index);
+
+	search.query = query.query.as_ref().map(|query| query.as_str());
+
+	let filter = query.meilisearch_filter();
+
+	search.filter = Some(meilisearch_sdk::search::Filter::new(sqlx::Either::Left(
+		filter.as_str(),
+	)));
+	search.facets = Some(meilisearch_sdk::search::Selectors::Some(&POST_FACETS));
+	search.limit = query.limit;
+	search.offset = query.offset;
+
+	let mut sort = vec![];
+	if let Some(qsort) = &query.sort {
+		sort.push(qsort.as_str());
+	} else {
+		sort.push("time:desc");
+	}
+	search.sort = Some(&sort);
+
+	let results = search
+		.execute::<Post>()
+		.await
+		.map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+	Ok(Json(FacetedPosts {
+		total: results.estimated_total_hits.unwrap_or(0),
+		posts: load_search_hits(results.hits, &state).await,
+		facets: results
+			.facet_distribution
+			.unwrap_or_default()
+			.into_iter()
+			.map(|(facet, values)| (facet, values.into_iter().collect()))
+			.collect(),
+	}))
+}
+
 pub async fn delete_post(
 	Path(id): Path<i32>,
 	user: User,
@@ -1081,6 +1620,8 @@ pub async fn delete_post(
 		return Err(StatusCode::UNAUTHORIZED);
 	}
 
+	let extracted = crate::api::changes::extracted_ids(post.id, &state).await;
+
 	for file in post.local_files {
 		_ = tokio::process::Command::new("rclone")
 			.arg("delete")
@@ -1100,67 +1641,8 @@ pub async fn delete_post(
 		.delete_document(post.id)
 		.await;
 
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("pvs"))
-		.with_filter(&format!("post={}", post.id))
-		.execute::<MeilisearchPv>()
-		.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("modules"))
-		.with_filter(&format!("post_id={}", post.id))
-		.execute::<MeilisearchModule>()
-		.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("cstm_items"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchCstmItem>()
-	.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("nc_songs"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchNcSong>()
-	.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("sprite_sets"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchDbEntry>()
-	.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("sprites"))
-		.with_filter(&format!("post_id={}", post.id))
-		.execute::<MeilisearchDbEntry>()
-		.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("aet_sets"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchDbEntry>()
-	.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("aet_scenes"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchDbEntry>()
-	.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(&state.meilisearch.index("objsets"))
-		.with_filter(&format!("post_id={}", post.id))
-		.execute::<MeilisearchDbEntry>()
-		.await;
-
-	_ = meilisearch_sdk::documents::DocumentDeletionQuery::new(
-		&state.meilisearch.index("textures"),
-	)
-	.with_filter(&format!("post_id={}", post.id))
-	.execute::<MeilisearchDbEntry>()
-	.await;
+	delete_extracted_data(post.id, &state).await;
+	crate::api::changes::log_extraction_changes(post.id, extracted, &state).await;
 
 	Ok(())
 }
@@ -1195,6 +1677,14 @@ pub async fn add_author(
 	.execute(&state.db)
 	.await;
 
+	if let Some(post) = Post::get_short(post.id, &state.db).await {
+		_ = state
+			.meilisearch
+			.index("posts")
+			.add_or_update(&[post], None)
+			.await;
+	};
+
 	Ok(Json(new_author))
 }
 
@@ -1229,6 +1719,14 @@ pub async fn remove_author(
 	.execute(&state.db)
 	.await;
 
+	if let Some(post) = Post::get_short(post.id, &state.db).await {
+		_ = state
+			.meilisearch
+			.index("posts")
+			.add_or_update(&[post], None)
+			.await;
+	};
+
 	Ok(())
 }
 
@@ -1504,6 +2002,8 @@ pub struct PostDetail {
 	pub conflict_users: BTreeMap<i64, User>,
 	pub requires_expatch: bool,
 	pub requires_nc: bool,
+	pub nc_compatibility: crate::api::compatibility::NcCompatibility,
+	pub related_posts: Vec<Post>,
 	pub has_required_sprites: bool,
 	pub has_optional_ftc_sprites: bool,
 	pub has_dml_pvtmb: bool,
@@ -1553,6 +2053,10 @@ pub async fn post_detail(
 			.unwrap_or(String::new());
 	}
 
+	Ok(Json(build_post_detail(post, &state).await))
+}
+
+pub async fn build_post_detail(post: Post, state: &AppState) -> PostDetail {
 	let Json(pvs) = search_pvs(
 		Query(crate::api::ids::SearchParams {
 			query: None,
@@ -1560,6 +2064,7 @@ pub async fn post_detail(
 			limit: Some(u32::MAX as usize),
 			offset: Some(0),
 		}),
+		Query(crate::api::ids::PvFilters::default()),
 		State(state.clone()),
 	)
 	.await
@@ -1642,11 +2147,14 @@ pub async fn post_detail(
 				let pv = Pv {
 					uid: BASE64_STANDARD.encode(pv.uid.to_ne_bytes()),
 					id: pv.pv_id,
+					preview_url: pv.preview_url(),
 					name: pv.song_name,
 					name_en: pv.song_name_en,
 					song_info: pv.song_info,
 					song_info_en: pv.song_info_en,
 					levels: pv.levels,
+					charts: pv.charts,
+					metadata: pv.metadata,
 					post,
 				};
 
@@ -2310,6 +2818,7 @@ pub async fn post_detail(
 		.iter()
 		.any(|pv| (pv.levels[3].is_some() || pv.levels[4].is_some()) && pv.levels[2].is_none());
 
+	// Kept separate from the compatibility matrix, the banner and the requires_nc lint depend on this rule
 	let requires_nc = nc_songs.nc_songs.iter().any(|nc_song| {
 		nc_songs.pvs.get(&nc_song.pv_id).map_or(false, |pvs| {
 			pvs.iter().all(|pv| pv.post.unwrap_or(-1) != post.id)
@@ -2319,6 +2828,8 @@ pub async fn post_detail(
 			.filter_map(|difficulty| difficulty.clone())
 			.all(|difficulty| difficulty.arcade.is_none())
 	});
+	let nc_compatibility = crate::api::compatibility::nc_compatibility(post.id, &pvs, &nc_songs);
+	let related_posts = Post::get_related(post.id, &state.db).await;
 
 	let required_pv_sprites = pvs
 		.pvs
@@ -2369,7 +2880,7 @@ pub async fn post_detail(
 			.iter()
 			.any(|(_, set)| set.starts_with("SPR_SEL_PVTMB_"));
 
-	Ok(Json(PostDetail {
+	PostDetail {
 		post,
 		pvs,
 		modules,
@@ -2403,8 +2914,10 @@ pub async fn post_detail(
 		conflict_users,
 		requires_expatch,
 		requires_nc,
+		nc_compatibility,
+		related_posts,
 		has_required_sprites,
 		has_optional_ftc_sprites,
 		has_dml_pvtmb,
-	}))
+	}
 }
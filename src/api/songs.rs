@@ -0,0 +1,225 @@
+use crate::AppState;
+use crate::api::ids::*;
+use crate::models::*;
+use axum::{extract::*, http::StatusCode};
+use itertools::*;
+use serde::{Deserialize, Serialize};
+use std::collections::*;
+use utoipa::{IntoParams, ToSchema};
+
+/// Lowercases and strips everything but letters and digits, so "Ievan Polkka (Short Ver.)" and "ievan polkka" compare equal
+fn normalize(text: &str) -> String {
+	let mut depth = 0;
+	let mut normalized = String::new();
+	for c in text.chars() {
+		match c {
+			'(' | '[' | '（' | '【' | '「' => depth += 1,
+			')' | ']' | '）' | '】' | '」' => depth = (depth - 1).max(0),
+			c if depth == 0 && c.is_alphanumeric() => normalized.extend(c.to_lowercase()),
+			_ => {}
+		}
+	}
+	normalized
+}
+
+/// Keys stored in the pvs index to find candidate matches for a song
+pub fn song_keys(song_name: &str, song_name_en: &str) -> Vec<String> {
+	[song_name, song_name_en]
+		.into_iter()
+		.map(normalize)
+		.filter(|key| !key.is_empty())
+		.sorted()
+		.dedup()
+		.collect()
+}
+
+fn credits(pv: &Pv, field: fn(&pv_db::SongInfo) -> Option<&String>) -> BTreeSet<String> {
+	[&pv.song_info, &pv.song_info_en]
+		.into_iter()
+		.flatten()
+		.filter_map(field)
+		.map(|credit| normalize(credit))
+		.filter(|credit| !credit.is_empty())
+		.collect()
+}
+
+/// Credits only contradict each other when both pvs set them and none of them overlap
+fn credits_agree(a: &Pv, b: &Pv, field: fn(&pv_db::SongInfo) -> Option<&String>) -> bool {
+	let a_credits = credits(a, field);
+	let b_credits = credits(b, field);
+	a_credits.is_empty()
+		|| b_credits.is_empty()
+		|| a_credits
+			.iter()
+			.cartesian_product(b_credits.iter())
+			.any(|(a, b)| a.contains(b.as_str()) || b.contains(a.as_str()))
+}
+
+/// Two pvs are the same song when a name matches and neither the music nor the lyrics credits contradict each other
+pub fn is_same_song(a: &Pv, b: &Pv) -> bool {
+	let a_keys = song_keys(&a.name, &a.name_en);
+	let b_keys = song_keys(&b.name, &b.name_en);
+	if !a_keys.iter().any(|key| b_keys.contains(key)) {
+		return false;
+	}
+
+	credits_agree(a, b, |song_info| song_info.music.as_ref())
+		&& credits_agree(a, b, |song_info| song_info.lyrics.as_ref())
+}
+
+async fn search_song_keys(keys: &BTreeSet<String>, state: &AppState) -> PvSearch {
+	if keys.is_empty() {
+		return PvSearch::default();
+	}
+
+	let filter = format!(
+		"song_keys IN [{}]",
+		keys.iter()
+			.map(|key| format!("\"{key}\""))
+			.intersperse(String::from(", "))
+			.collect::<String>()
+	);
+
+	search_pvs(
+		Query(SearchParams {
+			query: None,
+			filter: Some(filter),
+			limit: Some(u32::MAX as usize),
+			offset: Some(0),
+		}),
+		Query(PvFilters::default()),
+		State(state.clone()),
+	)
+	.await
+	.map(|Json(search)| search)
+	.unwrap_or_default()
+}
+
+#[derive(Serialize, Deserialize, Default, ToSchema)]
+pub struct SongVersions {
+	/// Versions of the song from other posts, keyed by the uid of the pv they match
+	pub versions: BTreeMap<String, Vec<Pv>>,
+	pub posts: BTreeMap<i32, Post>,
+}
+
+impl SongVersions {
+	/// All versions as a single search, for drawing with the pv list helpers
+	pub fn into_search(self) -> PvSearch {
+		let mut seen = BTreeSet::new();
+		PvSearch {
+			pvs: self
+				.versions
+				.into_values()
+				.flatten()
+				.filter(|pv| seen.insert(pv.uid.clone()))
+				.collect(),
+			nc_songs: BTreeMap::new(),
+			posts: self.posts,
+		}
+	}
+}
+
+pub async fn find_song_versions(pvs: &[Pv], state: &AppState) -> SongVersions {
+	let keys = pvs
+		.iter()
+		.flat_map(|pv| song_keys(&pv.name, &pv.name_en))
+		.collect::<BTreeSet<_>>();
+	let search = search_song_keys(&keys, state).await;
+
+	let mut versions = BTreeMap::new();
+	for pv in pvs {
+		let matches = search
+			.pvs
+			.iter()
+			.filter(|other| other.post != pv.post && is_same_song(pv, other))
+			.cloned()
+			.collect::<Vec<_>>();
+		if !matches.is_empty() {
+			versions.insert(pv.uid.clone(), matches);
+		}
+	}
+
+	let posts = search
+		.posts
+		.into_iter()
+		.filter(|(id, _)| versions.values().flatten().any(|pv| pv.post == Some(*id)))
+		.collect();
+
+	SongVersions { versions, posts }
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/ids/pvs/{post}/{pv_id}/versions",
+	params(
+		("post" = i32, Path, description = "Post id, or -1 for the base game"),
+		("pv_id" = i32, Path)
+	),
+	responses(
+		(status = 200, body = SongVersions, content_type = "application/json"),
+		(status = 400, body = String),
+		(status = 404, body = String)
+	)
+)]
+pub async fn song_versions(
+	Path((post, pv_id)): Path<(i32, i32)>,
+	State(state): State<AppState>,
+) -> Result<Json<SongVersions>, (StatusCode, String)> {
+	let Json(pvs) = search_pvs(
+		Query(SearchParams {
+			query: None,
+			filter: Some(format!("post={post} AND pv_id={pv_id}")),
+			limit: Some(1),
+			offset: Some(0),
+		}),
+		Query(PvFilters::default()),
+		State(state.clone()),
+	)
+	.await?;
+
+	if pvs.pvs.is_empty() {
+		return Err((StatusCode::NOT_FOUND, String::from("PV not found")));
+	}
+
+	Ok(Json(find_song_versions(&pvs.pvs, &state).await))
+}
+
+#[derive(Serialize, Deserialize, IntoParams)]
+pub struct SongQuery {
+	/// Song name in any language, compared after normalization
+	pub name: String,
+	/// Optional music credit to tell apart different songs with the same name
+	pub music: Option<String>,
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/ids/songs",
+	params(
+		SongQuery
+	),
+	responses(
+		(status = 200, body = PvSearch, content_type = "application/json")
+	)
+)]
+pub async fn search_songs(
+	Query(query): Query<SongQuery>,
+	State(state): State<AppState>,
+) -> Json<PvSearch> {
+	let keys = song_keys(&query.name, "").into_iter().collect();
+	let mut search = search_song_keys(&keys, &state).await;
+
+	if let Some(music) = query.music.as_ref().map(|music| normalize(music)) {
+		if !music.is_empty() {
+			search.pvs.retain(|pv| {
+				let credits = credits(pv, |song_info| song_info.music.as_ref());
+				credits.is_empty()
+					|| credits
+						.iter()
+						.any(|credit| credit.contains(&music) || music.contains(credit.as_str()))
+			});
+		}
+	}
+
+	Json(search)
+}
@@ -0,0 +1,283 @@
+use crate::AppState;
+use crate::api::ids::*;
+use crate::models::*;
+use axum::{extract::*, http::StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::*;
+use utoipa::{IntoParams, ToSchema};
+
+const MAX_SUGGESTIONS: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, IntoParams)]
+pub struct SuggestParams {
+	pub query: String,
+	/// Suggestions per index, at most 10
+	pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+#[serde(tag = "type")]
+pub enum Suggestion {
+	Post {
+		id: i32,
+		name: String,
+		url: String,
+	},
+	Song {
+		pv_id: i32,
+		name: String,
+		/// The post containing the song, none for base game songs
+		post: Option<i32>,
+		post_name: Option<String>,
+		url: String,
+	},
+	Module {
+		module_id: i32,
+		name: String,
+		post: Option<i32>,
+		post_name: Option<String>,
+		url: String,
+	},
+	CstmItem {
+		customize_item_id: i32,
+		name: String,
+		post: Option<i32>,
+		post_name: Option<String>,
+		url: String,
+	},
+	Author {
+		id: i64,
+		name: String,
+		url: String,
+	},
+}
+
+/// Picks whichever of the names contains the query, falling back to the first non empty one
+fn matching_name<'a>(names: impl IntoIterator<Item = &'a String>, query: &str) -> String {
+	let names = names
+		.into_iter()
+		.filter(|name| !name.is_empty())
+		.collect::<Vec<_>>();
+	let query = query.to_lowercase();
+	names
+		.iter()
+		.find(|name| name.to_lowercase().contains(&query))
+		.or(names.first())
+		.map(|name| name.to_string())
+		.unwrap_or_default()
+}
+
+fn post_url(post: Option<i32>) -> Option<String> {
+	post.map(|post| format!("/post/{post}"))
+}
+
+/// Search box autocomplete across posts, songs, modules, customize items and authors
+#[utoipa::path(
+	get,
+	path = "/api/v1/suggest",
+	params(SuggestParams),
+	responses(
+		(status = 200, body = Vec<Suggestion>, content_type = "application/json"),
+		(status = 500, body = String)
+	)
+)]
+pub async fn suggest(
+	Query(params): Query<SuggestParams>,
+	State(state): State<AppState>,
+) -> Result<Json<Vec<Suggestion>>, (StatusCode, String)> {
+	let query = params.query.trim();
+	if query.is_empty() {
+		return Ok(Json(Vec::new()));
+	}
+	let limit = params.limit.unwrap_or(5).clamp(1, MAX_SUGGESTIONS);
+
+	let posts_index = state.meilisearch.index("posts");
+	let pvs_index = state.meilisearch.index("pvs");
+	let modules_index = state.meilisearch.index("modules");
+	let cstm_items_index = state.meilisearch.index("cstm_items");
+
+	let mut post_search = meilisearch_sdk::search::SearchQuery::new(&posts_index);
+	post_search
+		.with_query(query)
+		.with_filter("private=false")
+		.with_limit(limit);
+	let mut pv_search = meilisearch_sdk::search::SearchQuery::new(&pvs_index);
+	pv_search.with_query(query).with_limit(limit);
+	let mut module_search = meilisearch_sdk::search::SearchQuery::new(&modules_index);
+	module_search.with_query(query).with_limit(limit);
+	let mut cstm_item_search = meilisearch_sdk::search::SearchQuery::new(&cstm_items_index);
+	cstm_item_search.with_query(query).with_limit(limit);
+
+	let mut search = meilisearch_sdk::search::MultiSearchQuery::new(&state.meilisearch);
+	search
+		.with_search_query(post_search)
+		.with_search_query(pv_search)
+		.with_search_query(module_search)
+		.with_search_query(cstm_item_search);
+
+	// Each index has its own document type, so hits are decoded per index
+	let results = search
+		.execute::<serde_json::Value>()
+		.await
+		.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+		.results;
+	let mut results = results.into_iter().map(|results| {
+		results
+			.hits
+			.into_iter()
+			.map(|hit| hit.result)
+			.collect::<Vec<_>>()
+	});
+	let mut next_hits = || results.next().unwrap_or_default();
+
+	let posts = next_hits()
+		.into_iter()
+		.filter_map(|hit| serde_json::from_value::<Post>(hit).ok())
+		.collect::<Vec<_>>();
+	let pvs = next_hits()
+		.into_iter()
+		.filter_map(|hit| serde_json::from_value::<MeilisearchPv>(hit).ok())
+		.collect::<Vec<_>>();
+	let modules = next_hits()
+		.into_iter()
+		.filter_map(|hit| serde_json::from_value::<MeilisearchModule>(hit).ok())
+		.collect::<Vec<_>>();
+	let cstm_items = next_hits()
+		.into_iter()
+		.filter_map(|hit| serde_json::from_value::<MeilisearchCstmItem>(hit).ok())
+		.collect::<Vec<_>>();
+
+	// Entries of private posts are in the id indexes too, so every referenced post is checked
+	let post_ids = pvs
+		.iter()
+		.map(|pv| pv.post)
+		.chain(modules.iter().map(|module| module.post_id))
+		.chain(cstm_items.iter().map(|cstm_item| cstm_item.post_id))
+		.filter(|post| *post != -1)
+		.collect::<BTreeSet<_>>()
+		.into_iter()
+		.collect::<Vec<_>>();
+	let entry_posts = sqlx::query!(
+		"SELECT id, name FROM posts WHERE id = ANY($1) AND NOT private",
+		&post_ids
+	)
+	.fetch_all(&state.db)
+	.await
+	.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+	.into_iter()
+	.map(|post| (post.id, post.name))
+	.collect::<BTreeMap<_, _>>();
+	let entry_post = |post_id: i32| -> Option<Option<(i32, String)>> {
+		if post_id == -1 {
+			Some(None)
+		} else {
+			entry_posts
+				.get(&post_id)
+				.map(|name| Some((post_id, name.clone())))
+		}
+	};
+
+	let mut suggestions = Vec::new();
+	let mut authors = BTreeMap::new();
+	let lowercase_query = query.to_lowercase();
+
+	for post in posts {
+		for author in &post.authors {
+			if author
+				.display_name
+				.to_lowercase()
+				.contains(&lowercase_query)
+				|| author.name.to_lowercase().contains(&lowercase_query)
+			{
+				authors.insert(author.id, author.display_name.clone());
+			}
+		}
+		suggestions.push(Suggestion::Post {
+			url: format!("/post/{}", post.id),
+			id: post.id,
+			name: post.name,
+		});
+	}
+
+	for pv in pvs {
+		let Some(post) = entry_post(pv.post) else {
+			continue;
+		};
+		suggestions.push(Suggestion::Song {
+			name: matching_name([&pv.song_name_en, &pv.song_name], query),
+			url: format!("/pv/{}/{}", pv.post, pv.pv_id),
+			pv_id: pv.pv_id,
+			post: post.as_ref().map(|(id, _)| *id),
+			post_name: post.map(|(_, name)| name),
+		});
+	}
+
+	for module in modules {
+		let Some(post) = entry_post(module.post_id) else {
+			continue;
+		};
+		let module = Module {
+			uid: String::new(),
+			post: post.as_ref().map(|(id, _)| *id),
+			id: module.module_id,
+			module: module.module,
+		};
+		let name = matching_name(
+			module.localized_names().into_iter().map(|(_, name)| name),
+			query,
+		);
+		suggestions.push(Suggestion::Module {
+			name: if name.is_empty() {
+				module.display_name()
+			} else {
+				name
+			},
+			url: module.url(),
+			module_id: module.id,
+			post: module.post,
+			post_name: post.map(|(_, name)| name),
+		});
+	}
+
+	for cstm_item in cstm_items {
+		let Some(post) = entry_post(cstm_item.post_id) else {
+			continue;
+		};
+		let names = &cstm_item.customize_item;
+		let name = matching_name(
+			[
+				&names.name_en,
+				&names.name,
+				&names.name_jp,
+				&names.name_cn,
+				&names.name_fr,
+				&names.name_ge,
+				&names.name_it,
+				&names.name_kr,
+				&names.name_sp,
+				&names.name_tw,
+			]
+			.into_iter()
+			.flatten(),
+			query,
+		);
+		suggestions.push(Suggestion::CstmItem {
+			name,
+			url: post_url(post.as_ref().map(|(id, _)| *id))
+				.unwrap_or_else(|| String::from("/cstm_items")),
+			customize_item_id: cstm_item.customize_item_id,
+			post: post.as_ref().map(|(id, _)| *id),
+			post_name: post.map(|(_, name)| name),
+		});
+	}
+
+	for (id, name) in authors.into_iter().take(limit) {
+		suggestions.push(Suggestion::Author {
+			url: format!("/user/{id}"),
+			id,
+			name,
+		});
+	}
+
+	Ok(Json(suggestions))
+}
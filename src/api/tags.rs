@@ -0,0 +1,256 @@
+use crate::AppState;
+use crate::models::*;
+use axum::{extract::*, http::StatusCode};
+use itertools::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+const MAX_TAGS: usize = 16;
+const MAX_TAG_LENGTH: usize = 48;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct TagRequest {
+	pub name: String,
+	/// Only used when the tag does not exist yet, or when set by an admin
+	pub category: Option<TagCategory>,
+}
+
+impl TagRequest {
+	// Quotes and backslashes would need escaping in meilisearch filters, so they aren't allowed
+	fn name(&self) -> Option<String> {
+		let name = self.name.split_whitespace().join(" ");
+		if name.is_empty() || name.chars().count() > MAX_TAG_LENGTH || name.contains(['"', '\\']) {
+			None
+		} else {
+			Some(name)
+		}
+	}
+}
+
+async fn update_post_index(post_id: i32, state: &AppState) {
+	if let Some(post) = Post::get_short(post_id, &state.db).await {
+		_ = state
+			.meilisearch
+			.index("posts")
+			.add_or_update(&[post], None)
+			.await;
+	}
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/tags",
+	responses(
+		(status = 200, body = Vec<Tag>, content_type = "application/json"),
+		(status = 500)
+	)
+)]
+pub async fn list_tags(State(state): State<AppState>) -> Result<Json<Vec<Tag>>, StatusCode> {
+	let tags = sqlx::query!(
+		r#"
+		SELECT * FROM tags
+		WHERE curated OR EXISTS (SELECT 1 FROM post_tags WHERE tag_id = tags.id)
+		ORDER BY category, name
+		"#
+	)
+	.fetch_all(&state.db)
+	.await
+	.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	Ok(Json(
+		tags.into_iter()
+			.map(|tag| Tag {
+				id: tag.id,
+				name: tag.name,
+				category: tag.category.into(),
+				curated: tag.curated,
+			})
+			.collect(),
+	))
+}
+
+/// Creates a curated tag, or curates an existing tag authors have been using
+#[utoipa::path(
+	post,
+	path = "/api/v1/tags",
+	request_body = TagRequest,
+	responses(
+		(status = 200, body = Tag, content_type = "application/json"),
+		(status = 400),
+		(status = 401)
+	)
+)]
+pub async fn create_tag(
+	user: User,
+	State(state): State<AppState>,
+	Json(request): Json<TagRequest>,
+) -> Result<Json<Tag>, StatusCode> {
+	if !user.is_admin(&state.config) {
+		return Err(StatusCode::UNAUTHORIZED);
+	}
+	let Some(name) = request.name() else {
+		return Err(StatusCode::BAD_REQUEST);
+	};
+	let category = request.category.unwrap_or(TagCategory::Other) as i32;
+
+	let tag = sqlx::query!(
+		r#"
+		INSERT INTO tags (name, category, curated)
+		VALUES ($1, $2, true)
+		ON CONFLICT (lower(name)) DO UPDATE SET name = excluded.name, category = excluded.category, curated = true
+		RETURNING id, name, category, curated
+		"#,
+		name,
+		category
+	)
+	.fetch_one(&state.db)
+	.await
+	.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	let posts = sqlx::query!("SELECT post_id FROM post_tags WHERE tag_id = $1", tag.id)
+		.fetch_all(&state.db)
+		.await
+		.unwrap_or_default();
+	for post in posts {
+		update_post_index(post.post_id, &state).await;
+	}
+
+	Ok(Json(Tag {
+		id: tag.id,
+		name: tag.name,
+		category: tag.category.into(),
+		curated: tag.curated,
+	}))
+}
+
+#[utoipa::path(
+	delete,
+	path = "/api/v1/tags/{id}",
+	params(
+		("id" = i32, Path)
+	),
+	responses(
+		(status = 200),
+		(status = 401)
+	)
+)]
+pub async fn delete_tag(
+	Path(id): Path<i32>,
+	user: User,
+	State(state): State<AppState>,
+) -> Result<(), StatusCode> {
+	if !user.is_admin(&state.config) {
+		return Err(StatusCode::UNAUTHORIZED);
+	}
+
+	let posts = sqlx::query!("SELECT post_id FROM post_tags WHERE tag_id = $1", id)
+		.fetch_all(&state.db)
+		.await
+		.unwrap_or_default();
+
+	sqlx::query!("DELETE FROM tags WHERE id = $1", id)
+		.execute(&state.db)
+		.await
+		.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	for post in posts {
+		update_post_index(post.post_id, &state).await;
+	}
+
+	Ok(())
+}
+
+/// Replaces the tags of a post, creating uncurated tags for any names that do not exist yet
+#[utoipa::path(
+	put,
+	path = "/api/v1/posts/{id}/tags",
+	params(
+		("id" = i32, Path)
+	),
+	request_body = Vec<TagRequest>,
+	responses(
+		(status = 200, body = Vec<Tag>, content_type = "application/json"),
+		(status = 400),
+		(status = 401),
+		(status = 404)
+	)
+)]
+pub async fn set_post_tags(
+	Path(id): Path<i32>,
+	user: User,
+	State(state): State<AppState>,
+	Json(requests): Json<Vec<TagRequest>>,
+) -> Result<Json<Vec<Tag>>, StatusCode> {
+	let Some(post) = Post::get_short(id, &state.db).await else {
+		return Err(StatusCode::NOT_FOUND);
+	};
+
+	if !post.authors.contains(&user) && !user.is_admin(&state.config) {
+		return Err(StatusCode::UNAUTHORIZED);
+	}
+	if requests.len() > MAX_TAGS {
+		return Err(StatusCode::BAD_REQUEST);
+	}
+
+	let mut transaction = state
+		.db
+		.begin()
+		.await
+		.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	let mut tag_ids = Vec::with_capacity(requests.len());
+	for request in &requests {
+		let Some(name) = request.name() else {
+			return Err(StatusCode::BAD_REQUEST);
+		};
+		let category = request.category.unwrap_or(TagCategory::Other) as i32;
+
+		// Existing tags keep their name and category, tags differing only in case are the same tag
+		let tag = sqlx::query!(
+			r#"
+			INSERT INTO tags (name, category)
+			VALUES ($1, $2)
+			ON CONFLICT (lower(name)) DO UPDATE SET name = tags.name
+			RETURNING id
+			"#,
+			name,
+			category
+		)
+		.fetch_one(&mut *transaction)
+		.await
+		.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+		tag_ids.push(tag.id);
+	}
+
+	sqlx::query!("DELETE FROM post_tags WHERE post_id = $1", post.id)
+		.execute(&mut *transaction)
+		.await
+		.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	sqlx::query!(
+		"INSERT INTO post_tags (post_id, tag_id) SELECT $1, * FROM UNNEST($2::int[]) ON CONFLICT DO NOTHING",
+		post.id,
+		&tag_ids
+	)
+	.execute(&mut *transaction)
+	.await
+	.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	// Uncurated tags only exist while some post uses them
+	sqlx::query!(
+		"DELETE FROM tags WHERE curated = false AND NOT EXISTS (SELECT 1 FROM post_tags WHERE tag_id = tags.id)"
+	)
+	.execute(&mut *transaction)
+	.await
+	.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	transaction
+		.commit()
+		.await
+		.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	update_post_index(post.id, &state).await;
+
+	Ok(Json(Tag::get_for_post(post.id, &state.db).await))
+}
@@ -0,0 +1,54 @@
+use divamodarchive2::api::ids::*;
+use divamodarchive2::*;
+
+const USAGE: &str = "Usage: import_base PATH VERSION [--replace] [--reextract-all]
+
+Imports the base game pvs, modules, customize items and db entries from a game data dump
+	PATH               Folder containing the rom_* folders of the game
+	VERSION            A label for this baseline, such as \"1.03 + DLC\"
+	--replace          Remove the existing baseline first instead of merging into it
+	--reextract-all    Re-extract every post afterwards, not only the ones sharing ids with the imported entries";
+
+#[tokio::main]
+async fn main() {
+	env_logger::init();
+
+	let mut positional = Vec::new();
+	let mut replace = false;
+	let mut reextract_all = false;
+	for arg in std::env::args().skip(1) {
+		match arg.as_str() {
+			"--replace" => replace = true,
+			"--reextract-all" => reextract_all = true,
+			"--help" | "-h" => {
+				println!("{USAGE}");
+				return;
+			}
+			_ => positional.push(arg),
+		}
+	}
+
+	let [path, version] = positional.as_slice() else {
+		eprintln!("{USAGE}");
+		std::process::exit(1);
+	};
+
+	let args = BaseGameImportArgs {
+		path: path.clone(),
+		version: version.clone(),
+		replace,
+		reextract_all,
+	};
+
+	let state = AppState::from_env().await;
+
+	let Some(import) = import_base_game(&args, &state).await else {
+		eprintln!("Failed to import base game data from {path}");
+		std::process::exit(1);
+	};
+
+	println!(
+		"Imported base game {}: {} pvs, {} modules, {} customize items, {} db entries",
+		import.version, import.pvs, import.modules, import.cstm_items, import.db_entries
+	);
+}
@@ -0,0 +1,86 @@
+use divamodarchive2::api::ids::*;
+use divamodarchive2::*;
+
+const USAGE: &str = "Usage: reextract [--post ID]... [--type TYPE] [--concurrency N] [--reset-indexes]
+
+Re-runs extraction for every post, or only the posts given with --post
+	--post ID          Only re-extract this post, may be given multiple times
+	--type TYPE        Only re-extract posts of this type (0 = Plugin, 1 = Module, 2 = Song, 4 = UI, 5 = Other)
+	--concurrency N    How many posts to extract at once, defaults to 4
+	--reset-indexes    Delete every meilisearch index and recreate its settings first";
+
+#[tokio::main]
+async fn main() {
+	env_logger::init();
+
+	let mut args = ReextractArgs::default();
+	let mut argv = std::env::args().skip(1);
+	while let Some(arg) = argv.next() {
+		match arg.as_str() {
+			"--post" => {
+				let Some(id) = argv.next().and_then(|id| id.parse().ok()) else {
+					eprintln!("--post requires a post id\n\n{USAGE}");
+					std::process::exit(1);
+				};
+				args.post_ids.push(id);
+			}
+			"--type" => {
+				let Some(post_type) = argv.next().and_then(|post_type| post_type.parse().ok())
+				else {
+					eprintln!("--type requires a post type\n\n{USAGE}");
+					std::process::exit(1);
+				};
+				args.post_type = Some(post_type);
+			}
+			"--concurrency" => {
+				let Some(concurrency) =
+					argv.next().and_then(|concurrency| concurrency.parse().ok())
+				else {
+					eprintln!("--concurrency requires a number\n\n{USAGE}");
+					std::process::exit(1);
+				};
+				args.concurrency = Some(concurrency);
+			}
+			"--reset-indexes" => args.reset_indexes = true,
+			"--help" | "-h" => {
+				println!("{USAGE}");
+				return;
+			}
+			_ => {
+				eprintln!("Unknown argument {arg}\n\n{USAGE}");
+				std::process::exit(1);
+			}
+		}
+	}
+
+	let state = AppState::from_env().await;
+
+	if args.reset_indexes {
+		println!("Resetting indexes");
+		reset_indexes(&state).await;
+	}
+
+	let post_ids = find_reextract_posts(&args, &state).await;
+	println!("Re-extracting {} posts", post_ids.len());
+
+	reextract_posts(post_ids, args.concurrency.unwrap_or(4), state.clone()).await;
+
+	if let Some(progress) = state.reextract_progress.lock().await.as_ref() {
+		println!(
+			"Finished re-extracting {} posts, {} failed",
+			progress.completed,
+			progress.failed.len()
+		);
+		if !progress.failed.is_empty() {
+			println!(
+				"Failed posts: {}",
+				progress
+					.failed
+					.iter()
+					.map(|id| id.to_string())
+					.collect::<Vec<_>>()
+					.join(", ")
+			);
+		}
+	}
+}
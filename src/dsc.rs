@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// Parameter counts of each opcode in the Future Tone script format used by Mega Mix+
+const PARAM_COUNTS: [usize; 107] = [
+	0, 1, 4, 2, 2, 2, 7, 4, 2, 6, 2, 1, 6, 2, 1, 1, 3, 2, 3, 5, 5, 4, 4, 5, 2, 0, 2, 4, 2, 2, 1,
+	21, 0, 3, 2, 5, 1, 1, 7, 1, 1, 2, 1, 2, 1, 2, 3, 3, 1, 2, 2, 3, 6, 6, 1, 1, 2, 3, 1, 2, 2, 4,
+	4, 1, 2, 1, 2, 1, 1, 3, 3, 3, 2, 1, 9, 3, 2, 4, 2, 3, 2, 24, 1, 2, 1, 3, 1, 3, 4, 1, 2, 6, 3,
+	2, 3, 3, 4, 1, 1, 3, 3, 4, 1, 3, 3, 8, 2,
+];
+
+const OP_END: usize = 0;
+const OP_TIME: usize = 1;
+const OP_TARGET: usize = 6;
+const OP_BAR_TIME_SET: usize = 28;
+const OP_PV_END: usize = 32;
+const OP_TARGET_FLYING_TIME: usize = 58;
+
+#[derive(Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct NoteCounts {
+	pub normal: u32,
+	pub hold: u32,
+	pub slide: u32,
+	pub slide_chain: u32,
+	pub chance: u32,
+	pub other: u32,
+}
+
+impl NoteCounts {
+	fn add(&mut self, target_type: i32) {
+		match target_type {
+			0..=3 => self.normal += 1,
+			4..=7 => self.hold += 1,
+			12 | 13 => self.slide += 1,
+			15 | 16 => self.slide_chain += 1,
+			18..=24 => self.chance += 1,
+			_ => self.other += 1,
+		}
+	}
+
+	pub fn total(&self) -> u32 {
+		self.normal + self.hold + self.slide + self.slide_chain + self.chance + self.other
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct BpmChange {
+	/// Seconds from the start of the chart
+	pub time: f32,
+	pub bpm: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct Chart {
+	pub script_file_name: String,
+	/// False when the pv_db references a script that is not in the archive
+	pub found: bool,
+	pub notes: NoteCounts,
+	pub total_notes: u32,
+	/// Length of the chart in seconds
+	pub duration: f32,
+	pub bpm_changes: Vec<BpmChange>,
+}
+
+impl Chart {
+	pub fn missing(script_file_name: &str) -> Self {
+		Self {
+			script_file_name: String::from(script_file_name),
+			found: false,
+			notes: NoteCounts::default(),
+			total_notes: 0,
+			duration: 0.0,
+			bpm_changes: Vec::new(),
+		}
+	}
+
+	pub fn parse(script_file_name: &str, data: &[u8]) -> Option<Self> {
+		let words = data
+			.chunks_exact(4)
+			.map(|word| i32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+			.collect::<Vec<_>>();
+
+		// Scripts from Future Tone onwards start with a format signature instead of an opcode
+		let mut i = match words.first() {
+			Some(signature) if *signature as u32 as usize >= PARAM_COUNTS.len() => 1,
+			Some(_) => 0,
+			None => return None,
+		};
+
+		let mut notes = NoteCounts::default();
+		let mut bpm_changes: Vec<BpmChange> = Vec::new();
+		let mut time = 0;
+		let mut end = 0;
+		while i < words.len() {
+			let op = words[i] as u32 as usize;
+			let Some(params) = PARAM_COUNTS
+				.get(op)
+				.and_then(|count| words.get(i + 1..i + 1 + count))
+			else {
+				break;
+			};
+			i += 1 + params.len();
+
+			let bpm = match op {
+				OP_END => break,
+				OP_TIME => {
+					time = params[0];
+					end = end.max(time);
+					None
+				}
+				OP_TARGET => {
+					notes.add(params[0]);
+					None
+				}
+				OP_BAR_TIME_SET => Some(params[0] as f32),
+				OP_TARGET_FLYING_TIME if params[0] > 0 => Some(240_000.0 / params[0] as f32),
+				OP_PV_END => {
+					end = time;
+					break;
+				}
+				_ => None,
+			};
+
+			if let Some(bpm) = bpm {
+				let bpm = (bpm * 100.0).round() / 100.0;
+				if bpm_changes.last().map_or(true, |change| change.bpm != bpm) {
+					bpm_changes.push(BpmChange {
+						time: time as f32 / 100_000.0,
+						bpm,
+					});
+				}
+			}
+		}
+
+		Some(Self {
+			script_file_name: String::from(script_file_name),
+			found: true,
+			total_notes: notes.total(),
+			notes,
+			duration: end as f32 / 100_000.0,
+			bpm_changes,
+		})
+	}
+
+	pub fn length(&self) -> String {
+		let seconds = self.duration.round() as u32;
+		format!("{}:{:02}", seconds / 60, seconds % 60)
+	}
+
+	pub fn bpm(&self) -> String {
+		let min = self
+			.bpm_changes
+			.iter()
+			.map(|change| change.bpm)
+			.fold(f32::MAX, f32::min);
+		let max = self
+			.bpm_changes
+			.iter()
+			.map(|change| change.bpm)
+			.fold(f32::MIN, f32::max);
+
+		if self.bpm_changes.is_empty() {
+			String::new()
+		} else if min == max {
+			format!("{}", min.round())
+		} else {
+			format!("{}-{}", min.round(), max.round())
+		}
+	}
+}
@@ -0,0 +1,403 @@
+#![allow(unstable_name_collisions)]
+pub mod api;
+pub mod dsc;
+pub mod models;
+pub mod previews;
+pub mod rss;
+pub mod sitemap;
+pub mod thumbnails;
+pub mod web;
+
+use meilisearch_sdk::client::*;
+use models::*;
+use sqlx::postgres::PgPoolOptions;
+
+#[derive(Clone)]
+pub struct Config {
+	pub decoding_key: jsonwebtoken::DecodingKey,
+	pub encoding_key: jsonwebtoken::EncodingKey,
+	pub discord_id: String,
+	pub discord_secret: String,
+	pub discord_bot_token: String,
+	pub cloudflare_image_token: String,
+	pub cloudflare_account_id: String,
+	pub admins: Vec<i64>,
+	pub storage_path: String,
+	/// Salts the hashed client ids of download events
+	pub download_salt: String,
+	/// Proxies allowed to set the client ip through cf-connecting-ip
+	pub trusted_proxies: Vec<api::downloads::IpRange>,
+}
+
+#[derive(Clone)]
+pub struct AppState {
+	pub config: Config,
+	pub db: sqlx::Pool<sqlx::Postgres>,
+	pub meilisearch: Client,
+	pub reextract_progress: std::sync::Arc<tokio::sync::Mutex<Option<api::ids::ReextractProgress>>>,
+}
+
+impl AppState {
+	pub async fn from_env() -> Self {
+		dotenvy::dotenv().expect(".env must exist");
+
+		let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must exist");
+		let db = PgPoolOptions::new()
+			.min_connections(4)
+			.max_connections(32)
+			.connect(&database_url)
+			.await
+			.expect("Could not connect to database");
+		sqlx::migrate!()
+			.run(&db)
+			.await
+			.expect("Unable to run migrations");
+
+		let secret_key = std::env::var("SECRET_KEY").expect("SECRET_KEY must exist");
+		let decoding_key = jsonwebtoken::DecodingKey::from_secret(secret_key.as_bytes());
+		let encoding_key = jsonwebtoken::EncodingKey::from_secret(secret_key.as_bytes());
+
+		let discord_id = std::env::var("DISCORD_ID").expect("DISCORD_ID must exist");
+		let discord_secret = std::env::var("DISCORD_SECRET").expect("DISCORD_SECRET must exist");
+		let discord_bot_token =
+			std::env::var("DISCORD_BOT_TOKEN").expect("DISCORD_BOT_TOKEN must exist");
+
+		let cloudflare_image_token =
+			std::env::var("CLOUDFLARE_IMAGE_TOKEN").expect("CLOUDFLARE_IMAGE_TOKEN must exist");
+		let cloudflare_account_id =
+			std::env::var("CLOUDFLARE_ACCOUNT_ID").expect("CLOUDFLARE_ACCOUNT_ID must exist");
+
+		let admins = std::env::var("ADMIN_IDS")
+			.expect("ADMIN_IDS must exist")
+			.split(',')
+			.map(|x| x.parse::<i64>().expect("Admin IDs must be i64"))
+			.collect();
+
+		let meilisearch_url = std::env::var("MEILISEARCH_URL").expect("MEILISEARCH_URL must exist");
+		let storage_path = std::env::var("STORAGE_PATH").expect("STORAGE_PATH must exist");
+
+		let download_salt = std::env::var("DOWNLOAD_SALT").expect("DOWNLOAD_SALT must exist");
+		let trusted_proxies = std::env::var("TRUSTED_PROXIES")
+			.expect("TRUSTED_PROXIES must exist")
+			.split(',')
+			.filter(|range| !range.trim().is_empty())
+			.map(|range| {
+				api::downloads::IpRange::parse(range).expect("Trusted proxies must be ip ranges")
+			})
+			.collect();
+
+		let config = Config {
+			decoding_key,
+			encoding_key,
+			discord_id,
+			discord_secret,
+			discord_bot_token,
+			cloudflare_image_token,
+			cloudflare_account_id,
+			admins,
+			storage_path,
+			download_salt,
+			trusted_proxies,
+		};
+
+		let client = meilisearch_sdk::client::Client::new(meilisearch_url, None::<&str>).unwrap();
+
+		AppState {
+			config,
+			db,
+			meilisearch: client,
+			reextract_progress: Default::default(),
+		}
+	}
+}
+
+pub async fn configure_indexes(client: &Client) {
+	let meilisearch_posts = client.index("posts");
+	let meilisearch_pvs = client.index("pvs");
+	let meilisearch_modules = client.index("modules");
+	let meilisearch_customize = client.index("cstm_items");
+	let meilisearch_reservations = client.index("reservations");
+	let meilisearch_nc = client.index("nc_songs");
+	let meilisearch_sprite_sets = client.index("sprite_sets");
+	let meilisearch_sprites = client.index("sprites");
+	let meilisearch_aet_sets = client.index("aet_sets");
+	let meilisearch_aet_scenes = client.index("aet_scenes");
+	let meilisearch_objsets = client.index("objsets");
+	let meilisearch_textures = client.index("textures");
+
+	meilisearch_posts
+		.set_searchable_attributes(&["authors.name", "name", "text"])
+		.await
+		.unwrap();
+	meilisearch_posts
+		.set_filterable_attributes(&[
+			"post_type",
+			"id",
+			"private",
+			"time",
+			"tags.name",
+			"tags.category",
+			"authors.id",
+			"authors.name",
+			"explicit",
+		])
+		.await
+		.unwrap();
+	meilisearch_posts
+		.set_sortable_attributes(&["download_count", "like_count", "time", "trending"])
+		.await
+		.unwrap();
+	meilisearch_posts
+		.set_faceting(&meilisearch_sdk::settings::FacetingSettings {
+			max_values_per_facet: crate::api::posts::MAX_FACET_VALUES,
+			sort_facet_values_by: Some(std::collections::BTreeMap::from([(
+				String::from("*"),
+				meilisearch_sdk::settings::FacetSortValue::Count,
+			)])),
+		})
+		.await
+		.unwrap();
+
+	meilisearch_pvs
+		.set_filterable_attributes(&[
+			"uid",
+			"post",
+			"pv_id",
+			"song_keys",
+			"levels",
+			"charts.found",
+			"charts.total_notes",
+			"charts.duration",
+			"metadata.bpm",
+			"metadata.performers",
+			"metadata.lyrics",
+			"metadata.lyrics_en",
+			"metadata.stars",
+			"metadata.difficulties.edition",
+			"metadata.difficulties.original",
+			"metadata.difficulties.extra",
+			"metadata.difficulties.slide",
+		])
+		.await
+		.unwrap();
+	meilisearch_pvs
+		.set_searchable_attributes(&[
+			"pv_id",
+			"song_name",
+			"song_name_en",
+			"song_info",
+			"song_info_en",
+		])
+		.await
+		.unwrap();
+	meilisearch_pvs
+		.set_sortable_attributes(&["post", "pv_id", "uid"])
+		.await
+		.unwrap();
+
+	meilisearch_modules
+		.set_filterable_attributes(&[
+			"uid",
+			"post_id",
+			"module_id",
+			"chara",
+			"cos.id",
+			"cos.items.id",
+			"cos.items.objset",
+		])
+		.await
+		.unwrap();
+	meilisearch_modules
+		.set_searchable_attributes(&[
+			"module_id",
+			"chara",
+			"name",
+			"name_jp",
+			"name_en",
+			"name_cn",
+			"name_fr",
+			"name_ge",
+			"name_it",
+			"name_kr",
+			"name_sp",
+			"name_tw",
+		])
+		.await
+		.unwrap();
+	meilisearch_modules
+		.set_sortable_attributes(&["post_id", "module_id", "uid"])
+		.await
+		.unwrap();
+
+	meilisearch_customize
+		.set_filterable_attributes(&["post_id", "customize_item_id", "bind_module", "uid"])
+		.await
+		.unwrap();
+	meilisearch_customize
+		.set_searchable_attributes(&[
+			"customize_item_id",
+			"chara",
+			"name",
+			"part",
+			"name_jp",
+			"name_en",
+			"name_cn",
+			"name_fr",
+			"name_ge",
+			"name_it",
+			"name_kr",
+			"name_sp",
+			"name_tw",
+		])
+		.await
+		.unwrap();
+	meilisearch_customize
+		.set_sortable_attributes(&["post_id", "customize_item_id", "uid"])
+		.await
+		.unwrap();
+
+	meilisearch_reservations
+		.set_filterable_attributes(&["id", "reservation_type", "user"])
+		.await
+		.unwrap();
+	meilisearch_reservations
+		.set_searchable_attributes(&["label", "id"])
+		.await
+		.unwrap();
+	meilisearch_reservations
+		.set_sortable_attributes(&["id"])
+		.await
+		.unwrap();
+
+	meilisearch_nc
+		.set_filterable_attributes(&["pv_id", "post_id"])
+		.await
+		.unwrap();
+	meilisearch_nc
+		.set_sortable_attributes(&["pv_id", "post_id"])
+		.await
+		.unwrap();
+
+	meilisearch_sprite_sets
+		.set_filterable_attributes(&["id", "post_id", "name", "uid"])
+		.await
+		.unwrap();
+	meilisearch_sprite_sets
+		.set_sortable_attributes(&["id", "post_id", "uid"])
+		.await
+		.unwrap();
+	meilisearch_sprite_sets
+		.set_searchable_attributes(&["name"])
+		.await
+		.unwrap();
+
+	meilisearch_sprites
+		.set_filterable_attributes(&["id", "post_id", "name", "uid"])
+		.await
+		.unwrap();
+	meilisearch_sprites
+		.set_sortable_attributes(&["id", "post_id", "uid"])
+		.await
+		.unwrap();
+	meilisearch_sprites
+		.set_searchable_attributes(&["name"])
+		.await
+		.unwrap();
+
+	meilisearch_aet_sets
+		.set_filterable_attributes(&["id", "post_id", "name", "uid"])
+		.await
+		.unwrap();
+	meilisearch_aet_sets
+		.set_sortable_attributes(&["id", "post_id", "uid"])
+		.await
+		.unwrap();
+	meilisearch_aet_sets
+		.set_searchable_attributes(&["name"])
+		.await
+		.unwrap();
+
+	meilisearch_aet_scenes
+		.set_filterable_attributes(&["id", "post_id", "name", "uid"])
+		.await
+		.unwrap();
+	meilisearch_aet_scenes
+		.set_sortable_attributes(&["id", "post_id", "uid"])
+		.await
+		.unwrap();
+	meilisearch_aet_scenes
+		.set_searchable_attributes(&["name"])
+		.await
+		.unwrap();
+
+	meilisearch_objsets
+		.set_filterable_attributes(&["id", "post_id", "name", "uid"])
+		.await
+		.unwrap();
+	meilisearch_objsets
+		.set_sortable_attributes(&["id", "post_id", "uid"])
+		.await
+		.unwrap();
+	meilisearch_objsets
+		.set_searchable_attributes(&["name"])
+		.await
+		.unwrap();
+
+	meilisearch_textures
+		.set_filterable_attributes(&["id", "post_id", "name", "uid"])
+		.await
+		.unwrap();
+	meilisearch_textures
+		.set_sortable_attributes(&["id", "post_id", "uid"])
+		.await
+		.unwrap();
+	meilisearch_textures
+		.set_searchable_attributes(&["name"])
+		.await
+		.unwrap();
+}
+
+pub const INDEXES: [&str; 12] = [
+	"posts",
+	"pvs",
+	"modules",
+	"cstm_items",
+	"reservations",
+	"nc_songs",
+	"sprite_sets",
+	"sprites",
+	"aet_sets",
+	"aet_scenes",
+	"objsets",
+	"textures",
+];
+
+// Deletes every index and recreates them from scratch, refilling them from postgres
+pub async fn reset_indexes(state: &AppState) {
+	for index in INDEXES {
+		_ = state.meilisearch.index(index).delete().await;
+	}
+
+	configure_indexes(&state.meilisearch).await;
+	reindex_posts(state).await;
+	api::ids::sync_extracted_data(None, state).await;
+	for i in 0..20 {
+		api::ids::optimise_reservations(i.into(), state.clone()).await;
+	}
+}
+
+pub async fn reindex_posts(state: &AppState) {
+	let posts = sqlx::query!("SELECT id FROM posts ORDER BY time DESC")
+		.fetch_all(&state.db)
+		.await;
+
+	if let Ok(posts) = posts {
+		let ids = posts.iter().map(|post| post.id).collect::<Vec<_>>();
+		let vec = Post::get_many_short(&ids, &state.db).await;
+		_ = state
+			.meilisearch
+			.index("posts")
+			.add_or_update(&vec, None)
+			.await;
+	}
+}
@@ -1,4 +1,7 @@
-use crate::{AppState, Config, api::ids::optimise_reservations};
+use crate::{
+	AppState, Config,
+	api::{ids::optimise_reservations, posts::update_missing_file_hashes},
+};
 use askama::Template;
 use axum::RequestPartsExt;
 use axum::extract::*;
@@ -99,6 +102,93 @@ impl std::fmt::Display for PostType {
 	}
 }
 
+#[repr(i32)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Clone, Copy, ToSchema)]
+pub enum TagCategory {
+	Genre = 0,
+	Vocalist = 1,
+	Difficulty = 2,
+	GameVersion = 3,
+	Requires = 4,
+	Other = 5,
+}
+
+impl From<i32> for TagCategory {
+	fn from(value: i32) -> Self {
+		match value {
+			0 => Self::Genre,
+			1 => Self::Vocalist,
+			2 => Self::Difficulty,
+			3 => Self::GameVersion,
+			4 => Self::Requires,
+			_ => Self::Other,
+		}
+	}
+}
+
+impl std::fmt::Display for TagCategory {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			TagCategory::Genre => "Genre",
+			TagCategory::Vocalist => "Vocalist",
+			TagCategory::Difficulty => "Difficulty",
+			TagCategory::GameVersion => "Game version",
+			TagCategory::Requires => "Requires",
+			TagCategory::Other => "Other",
+		})
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct Tag {
+	pub id: i32,
+	pub name: String,
+	pub category: TagCategory,
+	/// Curated tags are managed by admins and suggested to every author
+	pub curated: bool,
+}
+
+impl Tag {
+	pub async fn get_for_post(post_id: i32, db: &sqlx::Pool<sqlx::Postgres>) -> Vec<Self> {
+		sqlx::query!(
+			r#"
+			SELECT t.id, t.name, t.category, t.curated
+			FROM post_tags pt
+			JOIN tags t ON pt.tag_id = t.id
+			WHERE pt.post_id = $1
+			ORDER BY t.category, t.name
+			"#,
+			post_id
+		)
+		.fetch_all(db)
+		.await
+		.unwrap_or_default()
+		.into_iter()
+		.map(|tag| Tag {
+			id: tag.id,
+			name: tag.name,
+			category: tag.category.into(),
+			curated: tag.curated,
+		})
+		.collect()
+	}
+
+	// Names can't contain quotes or backslashes anymore, but older tags may still have them
+	pub fn search_url(&self) -> String {
+		format!(
+			"/search?filter={}",
+			url::form_urlencoded::byte_serialize(
+				format!(
+					"tags.name = \"{}\"",
+					self.name.replace('\\', "\\\\").replace('"', "\\\"")
+				)
+				.as_bytes()
+			)
+			.collect::<String>()
+		)
+	}
+}
+
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct Post {
 	pub id: i32,
@@ -124,6 +214,8 @@ pub struct Post {
 	pub private: bool,
 	pub explicit: bool,
 	pub explicit_reason: Option<String>,
+	#[serde(default)]
+	pub tags: Vec<Tag>,
 }
 
 impl Clone for Post {
@@ -147,6 +239,7 @@ impl Clone for Post {
 			private: self.private,
 			explicit: self.explicit,
 			explicit_reason: self.explicit_reason.clone(),
+			tags: self.tags.clone(),
 		}
 	}
 }
@@ -231,6 +324,12 @@ impl Iterator for CommentIterator<'_> {
 }
 
 impl Post {
+	/// Private posts are only visible to their authors and admins
+	pub fn visible_to(&self, user: Option<&User>, config: &Config) -> bool {
+		!self.private
+			|| user.is_some_and(|user| self.authors.contains(user) || user.is_admin(config))
+	}
+
 	pub async fn get_full(id: i32, db: &sqlx::Pool<sqlx::Postgres>) -> Option<Self> {
 		let post = sqlx::query!(
 			r#"
@@ -313,6 +412,7 @@ impl Post {
 				private: false,
 				explicit: dep.explicit,
 				explicit_reason: dep.explicit_reason,
+				tags: Vec::new(),
 			});
 
 			dep_descriptions.insert(dep.id, dep.description);
@@ -421,6 +521,7 @@ impl Post {
 			private: post.private,
 			explicit: post.explicit,
 			explicit_reason: post.explicit_reason,
+			tags: Tag::get_for_post(id, db).await,
 		})
 	}
 
@@ -472,8 +573,191 @@ impl Post {
 			private: post.private,
 			explicit: post.explicit,
 			explicit_reason: post.explicit_reason,
+			tags: Tag::get_for_post(id, db).await,
 		})
 	}
+
+	/// Public posts recommended alongside this one, highest scoring first
+	pub async fn get_related(id: i32, db: &sqlx::Pool<sqlx::Postgres>) -> Vec<Self> {
+		let ids = sqlx::query!(
+			r#"
+			SELECT rp.related_id
+			FROM related_posts rp
+			LEFT JOIN posts p ON rp.related_id = p.id
+			WHERE rp.post_id = $1 AND p.private = false
+			ORDER BY rp.score DESC, rp.related_id DESC
+			LIMIT $2
+			"#,
+			id,
+			RELATED_POSTS
+		)
+		.fetch_all(db)
+		.await
+		.unwrap_or_default()
+		.into_iter()
+		.map(|post| post.related_id)
+		.collect::<Vec<_>>();
+
+		Self::get_many_short(&ids, db).await
+	}
+
+	/// Loads posts with their authors and tags in a fixed number of queries, in the same order as `ids`
+	pub async fn get_many_short(ids: &[i32], db: &sqlx::Pool<sqlx::Postgres>) -> Vec<Self> {
+		if ids.is_empty() {
+			return Vec::new();
+		}
+
+		let Ok(posts) = sqlx::query!(
+			r#"
+			SELECT p.id, p.name, p.text, p.images, p.files, p.time, p.type as post_type, p.download_count, p.local_files, p.filesizes, p.private, p.explicit, p.explicit_reason, COALESCE(like_count.count, 0) AS "like_count!"
+			FROM posts p
+			LEFT JOIN (SELECT post_id, COUNT(*) as count FROM liked_posts GROUP BY post_id) AS like_count ON p.id = like_count.post_id
+			WHERE p.id = ANY($1)
+			"#,
+			ids
+		)
+		.fetch_all(db)
+		.await
+		else {
+			return Vec::new();
+		};
+
+		let mut authors: HashMap<i32, Vec<User>> = HashMap::new();
+		let author_rows = sqlx::query!(
+			r#"
+			SELECT pa.post_id, u.id, u.name, u.avatar, u.display_name, u.public_likes, u.theme, u.show_explicit
+			FROM post_authors pa
+			JOIN users u ON pa.user_id = u.id
+			WHERE pa.post_id = ANY($1)
+			"#,
+			ids
+		)
+		.fetch_all(db)
+		.await
+		.unwrap_or_default();
+		for author in author_rows {
+			authors.entry(author.post_id).or_default().push(User {
+				id: author.id,
+				name: author.name,
+				avatar: author.avatar,
+				display_name: author.display_name,
+				public_likes: author.public_likes,
+				theme: author.theme.into(),
+				show_explicit: author.show_explicit,
+			});
+		}
+
+		let mut tags: HashMap<i32, Vec<Tag>> = HashMap::new();
+		let tag_rows = sqlx::query!(
+			r#"
+			SELECT pt.post_id, t.id, t.name, t.category, t.curated
+			FROM post_tags pt
+			JOIN tags t ON pt.tag_id = t.id
+			WHERE pt.post_id = ANY($1)
+			ORDER BY t.category, t.name
+			"#,
+			ids
+		)
+		.fetch_all(db)
+		.await
+		.unwrap_or_default();
+		for tag in tag_rows {
+			tags.entry(tag.post_id).or_default().push(Tag {
+				id: tag.id,
+				name: tag.name,
+				category: tag.category.into(),
+				curated: tag.curated,
+			});
+		}
+
+		let mut posts = posts
+			.into_iter()
+			.map(|post| {
+				(
+					post.id,
+					Post {
+						id: post.id,
+						name: post.name,
+						text: post.text,
+						images: post.images,
+						files: post.files,
+						time: post.time.assume_offset(time::UtcOffset::UTC),
+						post_type: post.post_type.into(),
+						download_count: post.download_count,
+						like_count: post.like_count,
+						authors: authors.remove(&post.id).unwrap_or_default(),
+						dependencies: None,
+						dependency_descriptions: None,
+						comments: None,
+						local_files: post.local_files,
+						file_sizes: post.filesizes,
+						private: post.private,
+						explicit: post.explicit,
+						explicit_reason: post.explicit_reason,
+						tags: tags.remove(&post.id).unwrap_or_default(),
+					},
+				)
+			})
+			.collect::<HashMap<_, _>>();
+
+		ids.iter().filter_map(|id| posts.remove(id)).collect()
+	}
+
+	/// Same as `get_many_short` but also loads public dependencies, comments are left out
+	pub async fn get_many(ids: &[i32], db: &sqlx::Pool<sqlx::Postgres>) -> Vec<Self> {
+		let mut posts = Self::get_many_short(ids, db).await;
+		if posts.is_empty() {
+			return posts;
+		}
+
+		let dependency_rows = sqlx::query!(
+			r#"
+			SELECT pd.post_id, pd.dependency_id, pd.description
+			FROM post_dependencies pd
+			JOIN posts p ON pd.dependency_id = p.id
+			WHERE pd.post_id = ANY($1)
+			AND p.private = false
+			"#,
+			ids
+		)
+		.fetch_all(db)
+		.await
+		.unwrap_or_default();
+
+		let dependency_ids = dependency_rows
+			.iter()
+			.map(|dependency| dependency.dependency_id)
+			.collect::<std::collections::BTreeSet<_>>()
+			.into_iter()
+			.collect::<Vec<_>>();
+		let dependencies = Self::get_many_short(&dependency_ids, db)
+			.await
+			.into_iter()
+			.map(|post| (post.id, post))
+			.collect::<HashMap<_, _>>();
+
+		for post in &mut posts {
+			let mut deps = Vec::new();
+			let mut dep_descriptions = HashMap::new();
+			for dependency in dependency_rows
+				.iter()
+				.filter(|dependency| dependency.post_id == post.id)
+			{
+				let Some(dep) = dependencies.get(&dependency.dependency_id) else {
+					continue;
+				};
+				deps.push(dep.clone());
+				dep_descriptions.insert(dep.id, dependency.description.clone());
+			}
+
+			if !deps.is_empty() {
+				post.dependencies = Some(deps);
+				post.dependency_descriptions = Some(dep_descriptions);
+			}
+		}
+
+		posts
+	}
 }
 
 impl User {
@@ -700,11 +984,32 @@ pub fn routine_tasks(state: AppState) {
 	};
 
 	rt.block_on(async {
+		let hourly_state = state.clone();
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60 * 60));
+			loop {
+				interval.tick().await;
+				crate::api::downloads::update_trending(&hourly_state).await;
+			}
+		});
+
+		let daily_state = state.clone();
+		tokio::spawn(async move {
+			let mut interval =
+				tokio::time::interval(tokio::time::Duration::from_secs(60 * 60 * 24));
+			loop {
+				interval.tick().await;
+				crate::api::downloads::prune_download_events(&daily_state).await;
+				update_related_posts(&daily_state).await;
+			}
+		});
+
 		let mut interval =
 			tokio::time::interval(tokio::time::Duration::from_secs(60 * 60 * 24 * 7));
 		loop {
 			interval.tick().await;
 			tokio::spawn(update_users(state.clone()));
+			tokio::spawn(update_missing_file_hashes(state.clone()));
 			for i in 0..20 {
 				tokio::spawn(optimise_reservations(i.into(), state.clone()));
 			}
@@ -712,6 +1017,88 @@ pub fn routine_tasks(state: AppState) {
 	});
 }
 
+/// How many related posts are kept for each post
+const RELATED_POSTS: i64 = 8;
+/// Only each users most recent likes count as co-likes, so heavy likers don't make the join explode
+const RECENT_LIKES: i64 = 50;
+/// Posts whose related posts are replaced per transaction
+const RELATED_POSTS_BATCH: usize = 200;
+
+// Scores every pair of posts connected by co-likes, shared authors or dependencies, same type posts get a small bonus
+pub async fn update_related_posts(state: &AppState) {
+	let Ok(posts) = sqlx::query!("SELECT id FROM posts ORDER BY id")
+		.fetch_all(&state.db)
+		.await
+	else {
+		return;
+	};
+	let posts = posts.into_iter().map(|post| post.id).collect::<Vec<_>>();
+
+	for batch in posts.chunks(RELATED_POSTS_BATCH) {
+		let Ok(mut transaction) = state.db.begin().await else {
+			return;
+		};
+
+		let deleted = sqlx::query!("DELETE FROM related_posts WHERE post_id = ANY($1)", batch)
+			.execute(&mut *transaction)
+			.await;
+
+		let inserted = sqlx::query!(
+			r#"
+			WITH recent_likes AS (
+				SELECT user_id, post_id FROM (
+					SELECT user_id, post_id, ROW_NUMBER() OVER (PARTITION BY user_id ORDER BY time DESC NULLS LAST, post_id DESC) AS rank
+					FROM liked_posts
+				) likes
+				WHERE rank <= $3
+			), candidates AS (
+				SELECT a.post_id, b.post_id AS related_id, 1.0 AS score
+				FROM recent_likes a
+				JOIN recent_likes b ON a.user_id = b.user_id AND a.post_id <> b.post_id
+				WHERE a.post_id = ANY($1)
+				UNION ALL
+				SELECT a.post_id, b.post_id, 3.0
+				FROM post_authors a
+				JOIN post_authors b ON a.user_id = b.user_id AND a.post_id <> b.post_id
+				WHERE a.post_id = ANY($1)
+				UNION ALL
+				SELECT a.post_id, b.post_id, 2.0
+				FROM post_dependencies a
+				JOIN post_dependencies b ON a.dependency_id = b.dependency_id AND a.post_id <> b.post_id
+				WHERE a.post_id = ANY($1)
+				UNION ALL
+				SELECT post_id, dependency_id, 4.0 FROM post_dependencies
+				WHERE post_id <> dependency_id AND post_id = ANY($1)
+				UNION ALL
+				SELECT dependency_id, post_id, 4.0 FROM post_dependencies
+				WHERE post_id <> dependency_id AND dependency_id = ANY($1)
+			), scored AS (
+				SELECT c.post_id, c.related_id, SUM(c.score) + CASE WHEN p.type = r.type THEN 1.0 ELSE 0.0 END AS score
+				FROM candidates c
+				JOIN posts p ON p.id = c.post_id
+				JOIN posts r ON r.id = c.related_id
+				WHERE r.private = false
+				GROUP BY c.post_id, c.related_id, p.type, r.type
+			), ranked AS (
+				SELECT post_id, related_id, score, ROW_NUMBER() OVER (PARTITION BY post_id ORDER BY score DESC, related_id DESC) AS rank
+				FROM scored
+			)
+			INSERT INTO related_posts (post_id, related_id, score)
+			SELECT post_id, related_id, score FROM ranked WHERE rank <= $2
+			"#,
+			batch,
+			RELATED_POSTS,
+			RECENT_LIKES
+		)
+		.execute(&mut *transaction)
+		.await;
+
+		if deleted.is_ok() && inserted.is_ok() {
+			_ = transaction.commit().await;
+		}
+	}
+}
+
 pub async fn update_users(state: AppState) {
 	for user in sqlx::query!("SELECT u.id, u.name, u.avatar FROM users u")
 		.fetch_all(&state.db)
@@ -0,0 +1,74 @@
+use crate::AppState;
+use std::path::Path;
+use tokio::process::Command;
+
+const PREVIEW_LENGTH: f32 = 30.0;
+const FADE_LENGTH: f32 = 2.0;
+/// Songs come from uploads, so a file that makes ffmpeg hang is given up on after this long
+const FFMPEG_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+pub fn preview_path(storage_path: &str, post_id: i32, pv_id: i32) -> String {
+	format!("{storage_path}/previews/{post_id}/{pv_id}.mp3")
+}
+
+pub fn preview_url(post_id: i32, pv_id: i32) -> String {
+	format!("/previews/{post_id}/{pv_id}.mp3")
+}
+
+pub async fn delete_previews(post_id: i32, state: &AppState) {
+	_ = tokio::fs::remove_dir_all(format!("{}/previews/{post_id}", state.config.storage_path))
+		.await;
+}
+
+/// Transcodes a clip of the song starting at `start` seconds, returning whether a preview exists afterwards
+pub async fn render_preview(
+	post_id: i32,
+	pv_id: i32,
+	song: &Path,
+	start: f32,
+	state: &AppState,
+) -> bool {
+	let path = preview_path(&state.config.storage_path, post_id, pv_id);
+	if Path::new(&path).exists() {
+		return true;
+	}
+	if tokio::fs::create_dir_all(format!("{}/previews/{post_id}", state.config.storage_path))
+		.await
+		.is_err()
+	{
+		return false;
+	}
+
+	let mut command = Command::new("ffmpeg");
+	command
+		.kill_on_drop(true)
+		.arg("-y")
+		.arg("-loglevel")
+		.arg("error")
+		.arg("-ss")
+		.arg(format!("{start:.2}"))
+		.arg("-t")
+		.arg(format!("{PREVIEW_LENGTH}"))
+		.arg("-i")
+		.arg(song)
+		.arg("-vn")
+		.arg("-af")
+		.arg(format!(
+			"afade=t=in:d={FADE_LENGTH},afade=t=out:st={}:d={FADE_LENGTH}",
+			PREVIEW_LENGTH - FADE_LENGTH
+		))
+		.arg("-c:a")
+		.arg("libmp3lame")
+		.arg("-b:a")
+		.arg("128k")
+		.arg(&path);
+	// Dropping the output future on timeout drops the child, which kills ffmpeg
+	let output = tokio::time::timeout(FFMPEG_TIMEOUT, command.output()).await;
+
+	if output.is_ok_and(|output| output.is_ok_and(|output| output.status.success())) {
+		true
+	} else {
+		_ = tokio::fs::remove_file(&path).await;
+		false
+	}
+}
@@ -99,6 +99,37 @@ pub async fn sitemap(State(state): State<AppState>) -> Result<(HeaderMap, String
 		}
 	};
 
+	let pvs = sqlx::query!(
+		r#"
+		SELECT pv.post_id, pv.pv_id, p.time as "time?"
+		FROM pvs pv
+		LEFT JOIN posts p ON p.id = pv.post_id
+		WHERE pv.post_id = -1 OR p.private = false
+		ORDER BY pv.post_id, pv.pv_id
+		"#
+	)
+	.fetch_all(&state.db)
+	.await;
+	if let Ok(pvs) = pvs {
+		for pv in pvs {
+			let url = Url {
+				loc: Loc {
+					loc: format!("https://divamodarchive.com/pv/{}/{}", pv.post_id, pv.pv_id),
+				},
+				changefreq: Changefreq {
+					changefreq: String::from("monthly"),
+				},
+				priority: Priority {
+					priority: String::from("0.5"),
+				},
+				lastmod: pv.time.map(|time| Lastmod {
+					lastmod: time.date().to_string(),
+				}),
+			};
+			urls.push(url);
+		}
+	};
+
 	let users = sqlx::query!("SELECT DISTINCT u.id FROM users u LEFT JOIN post_authors pa ON pa.user_id = u.id WHERE pa.post_id IS NOT NULL ORDER BY id")
 		.fetch_all(&state.db)
 		.await;
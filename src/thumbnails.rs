@@ -0,0 +1,664 @@
+use crate::AppState;
+use crate::api::lint::ArchiveContents;
+use std::collections::*;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const THUMBNAIL_WIDTH: u32 = 256;
+/// Textures in uploads are untrusted, anything larger than the game itself uses is skipped
+const MAX_TEXTURE_SIZE: u32 = 8192;
+
+#[derive(Clone)]
+pub struct SpriteLocation {
+	pub farc: PathBuf,
+	pub set: String,
+}
+
+pub fn thumbnail_path(storage_path: &str, kind: &str, post_id: i32, id: i32) -> String {
+	format!("{storage_path}/thumbnails/{kind}/{post_id}/{id}.png")
+}
+
+pub async fn delete_thumbnails(post_id: i32, state: &AppState) {
+	for kind in ["pv", "module"] {
+		_ = tokio::fs::remove_dir_all(format!(
+			"{}/thumbnails/{kind}/{post_id}",
+			state.config.storage_path
+		))
+		.await;
+	}
+}
+
+// Has to run while the archive is still extracted as the sprite locations point into it
+pub async fn render_thumbnails(post_id: i32, archive: &ArchiveContents, state: &AppState) {
+	let mut targets = Vec::new();
+	for pv_db in &archive.pv_dbs {
+		for id in pv_db.data.pvs.keys() {
+			let id = *id as i32;
+			let sprites = [
+				format!("SPR_SEL_PVTMB_{:03}", id),
+				format!("SPR_SEL_PV{:03}_SONG_JK{:03}", id, id),
+			];
+			if let Some((sprite, location)) = sprites.into_iter().find_map(|sprite| {
+				Some((
+					sprite.clone(),
+					archive.sprite_locations.get(&sprite)?.clone(),
+				))
+			}) {
+				targets.push(("pv", id, sprite, location));
+			}
+		}
+	}
+	for module_db in &archive.module_dbs {
+		for id in module_db.data.modules.keys() {
+			let sprite = format!("SPR_SEL_MD{:03}CMN_MD_IMG", id);
+			if let Some(location) = archive.sprite_locations.get(&sprite) {
+				targets.push(("module", *id, sprite, location.clone()));
+			}
+		}
+	}
+
+	let storage_path = state.config.storage_path.clone();
+	_ = tokio::task::spawn_blocking(move || {
+		let mut sets: BTreeMap<PathBuf, Option<SpriteSet>> = BTreeMap::new();
+		for (kind, id, sprite, location) in targets {
+			let path = thumbnail_path(&storage_path, kind, post_id, id);
+			if Path::new(&path).exists() {
+				continue;
+			}
+
+			let set = sets
+				.entry(location.farc.clone())
+				.or_insert_with(|| SpriteSet::from_farc(&location.farc));
+			let Some(set) = set else {
+				continue;
+			};
+			let Some(image) = set.render(&sprite, &location.set) else {
+				continue;
+			};
+
+			let image = if image.width() > THUMBNAIL_WIDTH {
+				let height = image.height() * THUMBNAIL_WIDTH / image.width();
+				image::imageops::resize(
+					&image,
+					THUMBNAIL_WIDTH,
+					height.max(1),
+					image::imageops::FilterType::Triangle,
+				)
+			} else {
+				image
+			};
+
+			if let Some(parent) = Path::new(&path).parent() {
+				_ = std::fs::create_dir_all(parent);
+			}
+			_ = image.save_with_format(&path, image::ImageFormat::Png);
+		}
+	})
+	.await;
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+	Some(u32::from_le_bytes(
+		data.get(offset..offset + 4)?.try_into().ok()?,
+	))
+}
+
+fn read_u32_be(data: &[u8], offset: usize) -> Option<u32> {
+	Some(u32::from_be_bytes(
+		data.get(offset..offset + 4)?.try_into().ok()?,
+	))
+}
+
+fn read_f32(data: &[u8], offset: usize) -> Option<f32> {
+	Some(f32::from_le_bytes(
+		data.get(offset..offset + 4)?.try_into().ok()?,
+	))
+}
+
+fn read_string(data: &[u8], offset: usize) -> Option<String> {
+	let data = data.get(offset..)?;
+	let end = data.iter().position(|byte| *byte == 0)?;
+	String::from_utf8(data[..end].to_vec()).ok()
+}
+
+// Returns the first .bin file in a FArc (stored) or FArC (gzip) archive
+fn read_farc_bin(path: &Path) -> Option<Vec<u8>> {
+	let data = std::fs::read(path).ok()?;
+	let compressed = match data.get(0..4)? {
+		b"FArc" => false,
+		b"FArC" => true,
+		_ => return None,
+	};
+	let header_end = 8 + read_u32_be(&data, 4)? as usize;
+
+	let mut offset = 12;
+	while offset < header_end {
+		let name = read_string(&data, offset)?;
+		offset += name.len() + 1;
+		let file_offset = read_u32_be(&data, offset)? as usize;
+		let size = read_u32_be(&data, offset + 4)? as usize;
+		offset += if compressed { 12 } else { 8 };
+
+		if !name.ends_with(".bin") {
+			continue;
+		}
+
+		let file = data.get(file_offset..file_offset + size)?;
+		if file.starts_with(&[0x1F, 0x8B]) {
+			let mut out = Vec::new();
+			flate2::read::GzDecoder::new(file)
+				.read_to_end(&mut out)
+				.ok()?;
+			return Some(out);
+		}
+		return Some(file.to_vec());
+	}
+
+	None
+}
+
+struct Sprite {
+	name: String,
+	texture: usize,
+	x: f32,
+	y: f32,
+	width: f32,
+	height: f32,
+}
+
+struct SpriteSet {
+	sprites: Vec<Sprite>,
+	textures: Vec<Option<std::sync::Arc<image::RgbaImage>>>,
+}
+
+impl SpriteSet {
+	fn from_farc(path: &Path) -> Option<Self> {
+		let data = read_farc_bin(path)?;
+
+		let textures_offset = read_u32(&data, 4)? as usize;
+		let sprite_count = read_u32(&data, 12)? as usize;
+		let sprites_offset = read_u32(&data, 16)? as usize;
+		let sprite_names_offset = read_u32(&data, 24)? as usize;
+
+		// Counts come straight from the upload, so they're checked against the data before allocating
+		let fits = |offset: usize, size: usize| {
+			sprite_count
+				.checked_mul(size)
+				.and_then(|len| len.checked_add(offset))
+				.is_some_and(|end| end <= data.len())
+		};
+		if !fits(sprites_offset, 40) || !fits(sprite_names_offset, 4) {
+			return None;
+		}
+
+		let mut sprites = Vec::with_capacity(sprite_count);
+		for i in 0..sprite_count {
+			let offset = sprites_offset + i * 40;
+			let name_offset = read_u32(&data, sprite_names_offset + i * 4)? as usize;
+			sprites.push(Sprite {
+				name: read_string(&data, name_offset)?,
+				texture: read_u32(&data, offset)? as usize,
+				x: read_f32(&data, offset + 24)?,
+				y: read_f32(&data, offset + 28)?,
+				width: read_f32(&data, offset + 32)?,
+				height: read_f32(&data, offset + 36)?,
+			});
+		}
+
+		let referenced = sprites
+			.iter()
+			.map(|sprite| sprite.texture)
+			.collect::<BTreeSet<_>>();
+		Some(Self {
+			textures: decode_texture_set(data.get(textures_offset..)?, &referenced)?,
+			sprites,
+		})
+	}
+
+	fn render(&self, name: &str, set: &str) -> Option<image::RgbaImage> {
+		let short_name = name
+			.strip_prefix(set)
+			.map(|name| name.trim_start_matches('_'))
+			.unwrap_or(name);
+		let sprite = self
+			.sprites
+			.iter()
+			.find(|sprite| sprite.name == name || sprite.name == short_name)
+			.or_else(|| {
+				if self.sprites.len() == 1 {
+					self.sprites.first()
+				} else {
+					None
+				}
+			})?;
+
+		let texture: &image::RgbaImage = self.textures.get(sprite.texture)?.as_ref()?;
+		let x = sprite.x.max(0.0) as u32;
+		let y = sprite.y.max(0.0) as u32;
+		let width = (sprite.width as u32).min(texture.width().saturating_sub(x));
+		let height = (sprite.height as u32).min(texture.height().saturating_sub(y));
+		if width == 0 || height == 0 {
+			return None;
+		}
+
+		Some(image::imageops::crop_imm(texture, x, y, width, height).to_image())
+	}
+}
+
+struct Mipmap<'a> {
+	width: u32,
+	height: u32,
+	format: u32,
+	data: &'a [u8],
+}
+
+fn read_mipmap(data: &[u8]) -> Option<Mipmap<'_>> {
+	if data.get(0..4)? != b"TXP\x02" {
+		return None;
+	}
+	let width = read_u32(data, 4)?;
+	let height = read_u32(data, 8)?;
+	if width == 0 || height == 0 || width > MAX_TEXTURE_SIZE || height > MAX_TEXTURE_SIZE {
+		return None;
+	}
+	let size = read_u32(data, 20)? as usize;
+	Some(Mipmap {
+		width,
+		height,
+		format: read_u32(data, 12)?,
+		data: data.get(24..24usize.checked_add(size)?)?,
+	})
+}
+
+// Only the referenced textures are decoded, and textures sharing an offset are decoded once
+fn decode_texture_set(
+	data: &[u8],
+	referenced: &BTreeSet<usize>,
+) -> Option<Vec<Option<std::sync::Arc<image::RgbaImage>>>> {
+	if data.get(0..4)? != b"TXP\x03" {
+		return None;
+	}
+	let count = read_u32(data, 4)? as usize;
+	if count.checked_mul(4)?.checked_add(12)? > data.len() {
+		return None;
+	}
+
+	let mut decoded: BTreeMap<usize, Option<std::sync::Arc<image::RgbaImage>>> = BTreeMap::new();
+	let mut textures = Vec::with_capacity(count);
+	for i in 0..count {
+		if !referenced.contains(&i) {
+			textures.push(None);
+			continue;
+		}
+		let offset = read_u32(data, 12 + i * 4)? as usize;
+		let texture = match decoded.get(&offset) {
+			Some(texture) => texture.clone(),
+			None => {
+				let texture = decode_texture(data.get(offset..)?).map(std::sync::Arc::new);
+				decoded.insert(offset, texture.clone());
+				texture
+			}
+		};
+		textures.push(texture);
+	}
+	Some(textures)
+}
+
+// Textures are stored bottom up, the returned image is flipped so sprite coordinates can be used directly
+fn decode_texture(data: &[u8]) -> Option<image::RgbaImage> {
+	if data.get(0..4)? != b"TXP\x04" {
+		return None;
+	}
+	let mip_count = read_u32(data, 8)? & 0xFF;
+	let first = read_mipmap(data.get(read_u32(data, 12)? as usize..)?)?;
+
+	let pixels = if first.format == FORMAT_ATI2 && mip_count == 2 {
+		let second = read_mipmap(data.get(read_u32(data, 16)? as usize..)?)?;
+		decode_ycbcr(&first, &second)?
+	} else {
+		decode_pixels(&first)?
+	};
+
+	let image = image::RgbaImage::from_raw(first.width, first.height, pixels.concat())?;
+	Some(image::imageops::flip_vertical(&image))
+}
+
+const FORMAT_A8: u32 = 0;
+const FORMAT_RGB8: u32 = 1;
+const FORMAT_RGBA8: u32 = 2;
+const FORMAT_DXT1: u32 = 6;
+const FORMAT_DXT1A: u32 = 7;
+const FORMAT_DXT3: u32 = 8;
+const FORMAT_DXT5: u32 = 9;
+const FORMAT_ATI1: u32 = 10;
+const FORMAT_ATI2: u32 = 11;
+const FORMAT_L8: u32 = 12;
+const FORMAT_L8A8: u32 = 13;
+
+fn decode_pixels(mipmap: &Mipmap) -> Option<Vec<[u8; 4]>> {
+	let pixel_count = (mipmap.width as usize).checked_mul(mipmap.height as usize)?;
+	let data = mipmap.data;
+
+	match mipmap.format {
+		FORMAT_A8 => Some(
+			data.get(..pixel_count)?
+				.iter()
+				.map(|a| [255, 255, 255, *a])
+				.collect(),
+		),
+		FORMAT_RGB8 => Some(
+			data.get(..pixel_count * 3)?
+				.chunks_exact(3)
+				.map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+				.collect(),
+		),
+		FORMAT_RGBA8 => Some(
+			data.get(..pixel_count * 4)?
+				.chunks_exact(4)
+				.map(|rgba| [rgba[0], rgba[1], rgba[2], rgba[3]])
+				.collect(),
+		),
+		FORMAT_L8 => Some(
+			data.get(..pixel_count)?
+				.iter()
+				.map(|l| [*l, *l, *l, 255])
+				.collect(),
+		),
+		FORMAT_L8A8 => Some(
+			data.get(..pixel_count * 2)?
+				.chunks_exact(2)
+				.map(|la| [la[0], la[0], la[0], la[1]])
+				.collect(),
+		),
+		FORMAT_DXT1 | FORMAT_DXT1A => decode_blocks(mipmap, 8, |block| decode_bc1(block, true)),
+		FORMAT_DXT3 => decode_blocks(mipmap, 16, |block| {
+			let mut pixels = decode_bc1(&block[8..], false);
+			for (i, pixel) in pixels.iter_mut().enumerate() {
+				pixel[3] = ((block[i / 2] >> (i % 2 * 4)) & 0xF) * 17;
+			}
+			pixels
+		}),
+		FORMAT_DXT5 => decode_blocks(mipmap, 16, |block| {
+			let mut pixels = decode_bc1(&block[8..], false);
+			for (pixel, alpha) in pixels.iter_mut().zip(decode_bc4(block)) {
+				pixel[3] = alpha;
+			}
+			pixels
+		}),
+		FORMAT_ATI1 => decode_blocks(mipmap, 8, |block| {
+			decode_bc4(block).map(|red| [red, red, red, 255])
+		}),
+		FORMAT_ATI2 => decode_blocks(mipmap, 16, |block| {
+			let red = decode_bc4(block);
+			let green = decode_bc4(&block[8..]);
+			std::array::from_fn(|i| [red[i], green[i], 0, 255])
+		}),
+		_ => None,
+	}
+}
+
+fn decode_blocks(
+	mipmap: &Mipmap,
+	block_size: usize,
+	decode: impl Fn(&[u8]) -> [[u8; 4]; 16],
+) -> Option<Vec<[u8; 4]>> {
+	let width = mipmap.width as usize;
+	let height = mipmap.height as usize;
+	let blocks_x = width.div_ceil(4);
+	let blocks_y = height.div_ceil(4);
+	// Checked before allocating so a texture claiming to be huge can't allocate more than its data backs
+	if mipmap.data.len() < blocks_x.checked_mul(blocks_y)?.checked_mul(block_size)? {
+		return None;
+	}
+
+	let mut pixels = vec![[0u8; 4]; width * height];
+	for by in 0..blocks_y {
+		for bx in 0..blocks_x {
+			let offset = (by * blocks_x + bx) * block_size;
+			let block = decode(mipmap.data.get(offset..offset + block_size)?);
+			for (i, pixel) in block.iter().enumerate() {
+				let x = bx * 4 + i % 4;
+				let y = by * 4 + i / 4;
+				if x < width && y < height {
+					pixels[y * width + x] = *pixel;
+				}
+			}
+		}
+	}
+
+	Some(pixels)
+}
+
+fn rgb565(colour: u16) -> [u8; 4] {
+	let r = ((colour >> 11) & 0x1F) as u8;
+	let g = ((colour >> 5) & 0x3F) as u8;
+	let b = (colour & 0x1F) as u8;
+	[
+		(r << 3) | (r >> 2),
+		(g << 2) | (g >> 4),
+		(b << 3) | (b >> 2),
+		255,
+	]
+}
+
+fn mix(a: [u8; 4], b: [u8; 4], a_weight: u16, b_weight: u16) -> [u8; 4] {
+	let total = a_weight + b_weight;
+	std::array::from_fn(|i| ((a[i] as u16 * a_weight + b[i] as u16 * b_weight) / total) as u8)
+}
+
+fn decode_bc1(block: &[u8], allow_alpha: bool) -> [[u8; 4]; 16] {
+	let c0 = u16::from_le_bytes([block[0], block[1]]);
+	let c1 = u16::from_le_bytes([block[2], block[3]]);
+	let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+	let a = rgb565(c0);
+	let b = rgb565(c1);
+	let colours = if c0 > c1 || !allow_alpha {
+		[a, b, mix(a, b, 2, 1), mix(a, b, 1, 2)]
+	} else {
+		[a, b, mix(a, b, 1, 1), [0, 0, 0, 0]]
+	};
+
+	std::array::from_fn(|i| colours[((indices >> (i * 2)) & 0x3) as usize])
+}
+
+fn decode_bc4(block: &[u8]) -> [u8; 16] {
+	let a0 = block[0] as u16;
+	let a1 = block[1] as u16;
+	let mut indices = 0u64;
+	for (i, byte) in block[2..8].iter().enumerate() {
+		indices |= (*byte as u64) << (i * 8);
+	}
+
+	let values: [u8; 8] = if a0 > a1 {
+		std::array::from_fn(|i| match i {
+			0 => a0 as u8,
+			1 => a1 as u8,
+			i => ((a0 * (8 - i as u16) + a1 * (i as u16 - 1)) / 7) as u8,
+		})
+	} else {
+		std::array::from_fn(|i| match i {
+			0 => a0 as u8,
+			1 => a1 as u8,
+			6 => 0,
+			7 => 255,
+			i => ((a0 * (6 - i as u16) + a1 * (i as u16 - 1)) / 5) as u8,
+		})
+	};
+
+	std::array::from_fn(|i| values[((indices >> (i * 3)) & 0x7) as usize])
+}
+
+// Mega Mix+ stores most sprites as an ATI2 luma and alpha texture with a half size ATI2 chroma mipmap
+fn decode_ycbcr(luma: &Mipmap, chroma: &Mipmap) -> Option<Vec<[u8; 4]>> {
+	let luma_pixels = decode_pixels(luma)?;
+	let chroma_pixels = decode_pixels(chroma)?;
+	let width = luma.width as usize;
+	let chroma_width = chroma.width.max(1) as usize;
+	let chroma_height = chroma.height.max(1) as usize;
+
+	Some(
+		luma_pixels
+			.iter()
+			.enumerate()
+			.map(|(i, pixel)| {
+				let x = (i % width) / 2;
+				let y = (i / width) / 2;
+				let chroma = chroma_pixels
+					[y.min(chroma_height - 1) * chroma_width + x.min(chroma_width - 1)];
+
+				let y = pixel[0] as f32 / 255.0;
+				let cb = chroma[0] as f32 / 255.0 * 1.003922 - 0.503929;
+				let cr = chroma[1] as f32 / 255.0 * 1.003922 - 0.503929;
+				let to_u8 = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+				[
+					to_u8(y + 1.5748 * cr),
+					to_u8(y - 0.1873 * cb - 0.4681 * cr),
+					to_u8(y + 1.8556 * cb),
+					pixel[1],
+				]
+			})
+			.collect(),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const RED: [u8; 4] = [255, 0, 0, 255];
+	const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+	fn mipmap(width: u32, height: u32, format: u32, data: &[u8]) -> Mipmap<'_> {
+		Mipmap {
+			width,
+			height,
+			format,
+			data,
+		}
+	}
+
+	#[test]
+	fn bc1_four_colours() {
+		// c0 red, c1 blue, indices 0, 1, 2, 3 repeating
+		let block = [0x00, 0xF8, 0x1F, 0x00, 0xE4, 0xE4, 0xE4, 0xE4];
+		let pixels = decode_bc1(&block, true);
+		assert_eq!(pixels[0], RED);
+		assert_eq!(pixels[1], BLUE);
+		assert_eq!(pixels[2], [170, 0, 85, 255]);
+		assert_eq!(pixels[3], [85, 0, 170, 255]);
+	}
+
+	#[test]
+	fn bc1_transparent() {
+		// c0 <= c1 switches to three colours and transparent black
+		let block = [0x1F, 0x00, 0x00, 0xF8, 0xE4, 0xE4, 0xE4, 0xE4];
+		let pixels = decode_bc1(&block, true);
+		assert_eq!(pixels[2], [127, 0, 127, 255]);
+		assert_eq!(pixels[3], [0, 0, 0, 0]);
+
+		// DXT3 and DXT5 colour blocks always use four colours
+		let pixels = decode_bc1(&block, false);
+		assert_eq!(pixels[3], [170, 0, 85, 255]);
+	}
+
+	#[test]
+	fn bc4_eight_values() {
+		// Indices 0 through 7 for the first eight pixels
+		let block = [255, 0, 0x88, 0xC6, 0xFA, 0x00, 0x00, 0x00];
+		let values = decode_bc4(&block);
+		assert_eq!(values[..8], [255, 0, 218, 182, 145, 109, 72, 36]);
+		assert!(values[8..].iter().all(|value| *value == 255));
+	}
+
+	#[test]
+	fn bc4_six_values() {
+		let block = [0, 255, 0x88, 0xC6, 0xFA, 0x00, 0x00, 0x00];
+		let values = decode_bc4(&block);
+		assert_eq!(values[..8], [0, 255, 51, 102, 153, 204, 0, 255]);
+	}
+
+	#[test]
+	fn blocks_crop_to_size() {
+		let block = [0x00, 0xF8, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x00];
+		let pixels = decode_pixels(&mipmap(2, 3, FORMAT_DXT1, &block)).unwrap();
+		assert_eq!(pixels, vec![RED; 6]);
+	}
+
+	#[test]
+	fn blocks_reject_short_data() {
+		// A 65535 square texture needs far more than one block of data
+		let block = [0; 8];
+		assert!(decode_pixels(&mipmap(65535, 65535, FORMAT_DXT1, &block)).is_none());
+		assert!(decode_pixels(&mipmap(8, 4, FORMAT_DXT5, &[0; 16])).is_none());
+		assert!(decode_pixels(&mipmap(4, 4, FORMAT_RGBA8, &[0; 63])).is_none());
+	}
+
+	#[test]
+	fn mipmap_rejects_oversized() {
+		let mut data = b"TXP\x02".to_vec();
+		data.extend(65535u32.to_le_bytes());
+		data.extend(65535u32.to_le_bytes());
+		data.extend(FORMAT_DXT1.to_le_bytes());
+		data.extend(0u32.to_le_bytes());
+		data.extend(8u32.to_le_bytes());
+		data.extend([0; 8]);
+		assert!(read_mipmap(&data).is_none());
+
+		data[4..8].copy_from_slice(&4u32.to_le_bytes());
+		data[8..12].copy_from_slice(&4u32.to_le_bytes());
+		let mipmap = read_mipmap(&data).unwrap();
+		assert_eq!((mipmap.width, mipmap.height, mipmap.data.len()), (4, 4, 8));
+	}
+
+	#[test]
+	fn ycbcr_grey() {
+		// Luma of 128 with alpha 255 and neutral chroma decodes to opaque grey
+		let luma = [128, 128, 0, 0, 0, 0, 0, 0, 255, 255, 0, 0, 0, 0, 0, 0];
+		let chroma = [128, 128, 0, 0, 0, 0, 0, 0, 128, 128, 0, 0, 0, 0, 0, 0];
+		let pixels = decode_ycbcr(
+			&mipmap(4, 4, FORMAT_ATI2, &luma),
+			&mipmap(2, 2, FORMAT_ATI2, &chroma),
+		)
+		.unwrap();
+		assert_eq!(pixels.len(), 16);
+		for pixel in pixels {
+			assert_eq!(pixel, [128, 128, 128, 255]);
+		}
+	}
+
+	#[test]
+	fn texture_set_oversized_count() {
+		let mut data = b"TXP\x03".to_vec();
+		data.extend(u32::MAX.to_le_bytes());
+		data.extend(0u32.to_le_bytes());
+		assert!(decode_texture_set(&data, &BTreeSet::from([0])).is_none());
+	}
+
+	#[test]
+	fn texture_set_shared_offsets() {
+		// Three entries pointing at the same 1x1 texture, the middle one unreferenced
+		let mut data = b"TXP\x03".to_vec();
+		data.extend(3u32.to_le_bytes());
+		data.extend(0u32.to_le_bytes());
+		for _ in 0..3 {
+			data.extend(24u32.to_le_bytes());
+		}
+		data.extend(b"TXP\x04");
+		data.extend(1u32.to_le_bytes());
+		data.extend(1u32.to_le_bytes());
+		data.extend(16u32.to_le_bytes());
+		data.extend(b"TXP\x02");
+		for value in [1, 1, FORMAT_RGBA8, 0, 4] {
+			data.extend(u32::to_le_bytes(value));
+		}
+		data.extend(RED);
+
+		let textures = decode_texture_set(&data, &BTreeSet::from([0, 2])).unwrap();
+		assert_eq!(textures.len(), 3);
+		assert!(textures[1].is_none());
+		let first = textures[0].as_ref().unwrap();
+		assert!(std::sync::Arc::ptr_eq(first, textures[2].as_ref().unwrap()));
+		assert_eq!(first.get_pixel(0, 0).0, RED);
+	}
+}
@@ -1,4 +1,8 @@
+use crate::api::compatibility::{ChartSource, NcCompatibility};
+use crate::api::dashboard::AuthorDashboard;
+use crate::api::downloads::{DownloadInterval, DownloadStatsParams};
 use crate::api::ids::*;
+use crate::api::lint::LintSeverity;
 use crate::models::*;
 use crate::{AppState, Config};
 use askama::Template;
@@ -25,11 +29,15 @@ pub fn route(state: AppState) -> Router {
 		.route("/post/{id}/report", get(report))
 		.route("/liked/{id}", get(liked))
 		.route("/user/{id}", get(user))
+		.route("/user/{id}/dashboard", get(dashboard))
 		.route("/reservations/{id}", get(user_reservations))
 		.route("/upload", get(upload))
 		.route("/settings", get(settings))
 		.route("/pvs", get(pvs))
+		.route("/pv/{post}/{pv_id}", get(pv_detail))
 		.route("/modules", get(modules))
+		.route("/module/{post}/{module_id}", get(module_detail))
+		.route("/cos/{chara}/{cos_id}", get(cos_detail))
 		.route("/cstm_items", get(cstm_items))
 		.route("/pv_spreadsheet", get(pv_spreadsheet))
 		.route("/module_spreadsheet", get(module_spreadsheet))
@@ -53,9 +61,92 @@ pub fn route(state: AppState) -> Router {
 		.route("/reserve", get(reserve))
 		//.route("/admin", get(admin))
 		.layer(axum::middleware::from_fn(axum_html_minifier::html_minifier))
+		.route("/thumbnails/{kind}/{post}/{file}", get(thumbnail))
+		.route("/previews/{post}/{file}", get(preview))
 		.with_state(state)
 }
 
+// Files of private posts are only served to their authors and admins, base game files always are
+async fn check_post_access(
+	post_id: i32,
+	user: Result<User, ErrorTemplate>,
+	state: &AppState,
+) -> Result<(), StatusCode> {
+	if post_id == -1 {
+		return Ok(());
+	}
+
+	let Some(post) = Post::get_short(post_id, &state.db).await else {
+		return Err(StatusCode::NOT_FOUND);
+	};
+	if post.private {
+		let Ok(user) = user else {
+			return Err(StatusCode::UNAUTHORIZED);
+		};
+		if !post.authors.contains(&user) && !user.is_admin(&state.config) {
+			return Err(StatusCode::UNAUTHORIZED);
+		}
+	}
+
+	Ok(())
+}
+
+async fn thumbnail(
+	Path((kind, post_id, file)): Path<(String, i32, String)>,
+	user: Result<User, ErrorTemplate>,
+	State(state): State<AppState>,
+) -> Result<impl IntoResponse, StatusCode> {
+	if kind != "pv" && kind != "module" {
+		return Err(StatusCode::NOT_FOUND);
+	}
+	let Some(id) = file
+		.strip_suffix(".png")
+		.and_then(|id| id.parse::<i32>().ok())
+	else {
+		return Err(StatusCode::NOT_FOUND);
+	};
+
+	check_post_access(post_id, user, &state).await?;
+
+	let data = tokio::fs::read(crate::thumbnails::thumbnail_path(
+		&state.config.storage_path,
+		&kind,
+		post_id,
+		id,
+	))
+	.await
+	.map_err(|_| StatusCode::NOT_FOUND)?;
+
+	Ok(([(CONTENT_TYPE, "image/png")], data))
+}
+
+// ServeFile handles range requests so players can seek
+async fn preview(
+	Path((post_id, file)): Path<(i32, String)>,
+	user: Result<User, ErrorTemplate>,
+	State(state): State<AppState>,
+	request: Request,
+) -> Result<Response, StatusCode> {
+	let Some(pv_id) = file
+		.strip_suffix(".mp3")
+		.and_then(|id| id.parse::<i32>().ok())
+	else {
+		return Err(StatusCode::NOT_FOUND);
+	};
+
+	check_post_access(post_id, user, &state).await?;
+
+	let response = tower_http::services::ServeFile::new(format!(
+		"{}/previews/{post_id}/{pv_id}.mp3",
+		state.config.storage_path
+	))
+	.try_call(request)
+	.await
+	.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+	Ok(response.map(axum::body::Body::new).into_response())
+}
+
 const DIFFICULTY_COLOURS: [&'static str; 5] = [
 	"--diva-easy",
 	"--diva-normal",
@@ -324,12 +415,8 @@ async fn liked(
 		status: StatusCode::INTERNAL_SERVER_ERROR,
 	})?;
 
-	let mut posts = Vec::new();
-	for post in liked_posts {
-		if let Some(post) = Post::get_short(post.id, &state.db).await {
-			posts.push(post);
-		}
-	}
+	let ids = liked_posts.iter().map(|post| post.id).collect::<Vec<_>>();
+	let posts = Post::get_many_short(&ids, &state.db).await;
 
 	Ok(LikedTemplate { base, posts, owner })
 }
@@ -358,13 +445,30 @@ async fn user(
 		});
 	};
 
-	let user_posts = sqlx::query!(
+	let Json(mut posts) = crate::api::posts::search_posts(
+		Query(crate::api::posts::SearchParams {
+			query: None,
+			sort: None,
+			filter: None,
+			author: Some(id),
+			limit: Some(u32::MAX as usize),
+			offset: Some(0),
+		}),
+		State(state.clone()),
+	)
+	.await
+	.map_err(|(status, _)| ErrorTemplate {
+		base: base.clone(),
+		status,
+	})?;
+
+	// Private posts are never returned by search, so authors and admins get them from postgres
+	let private_posts = sqlx::query!(
 		r#"
 		SELECT p.id
 		FROM post_authors pa
 		LEFT JOIN posts p ON pa.post_id = p.id
-		WHERE pa.user_id = $1
-		ORDER BY p.time DESC
+		WHERE pa.user_id = $1 AND p.private = true
 		"#,
 		id
 	)
@@ -375,19 +479,15 @@ async fn user(
 		status: StatusCode::BAD_REQUEST,
 	})?;
 
-	let mut posts = Vec::new();
-	for post in user_posts {
-		if let Some(post) = Post::get_short(post.id, &state.db).await {
-			if post.private {
-				if !base.user.as_ref().map_or(false, |user| {
-					post.authors.contains(user) || user.is_admin(&state.config)
-				}) {
-					continue;
-				}
-			}
+	let ids = private_posts.iter().map(|post| post.id).collect::<Vec<_>>();
+	for post in Post::get_many_short(&ids, &state.db).await {
+		if base.user.as_ref().map_or(false, |user| {
+			post.authors.contains(user) || user.is_admin(&state.config)
+		}) {
 			posts.push(post);
 		}
 	}
+	posts.sort_by(|a, b| b.time.cmp(&a.time));
 
 	let (total_likes, total_downloads) = posts.iter().fold((0, 0), |acc, post| {
 		(acc.0 + post.like_count, acc.1 + post.download_count)
@@ -415,6 +515,52 @@ async fn user(
 	})
 }
 
+#[derive(Template, WebTemplate)]
+#[template(path = "dashboard.html")]
+struct DashboardTemplate {
+	base: BaseTemplate,
+	owner: User,
+	dashboard: AuthorDashboard,
+}
+
+async fn dashboard(
+	Path(id): Path<i64>,
+	Query(params): Query<DownloadStatsParams>,
+	base: BaseTemplate,
+	State(state): State<AppState>,
+) -> Result<DashboardTemplate, ErrorTemplate> {
+	if !base
+		.user
+		.as_ref()
+		.is_some_and(|user| user.id == id || user.is_admin(&state.config))
+	{
+		return Err(ErrorTemplate {
+			base,
+			status: StatusCode::UNAUTHORIZED,
+		});
+	}
+
+	let Some(owner) = User::get(id, &state.db).await else {
+		return Err(ErrorTemplate {
+			base,
+			status: StatusCode::BAD_REQUEST,
+		});
+	};
+
+	let dashboard = crate::api::dashboard::author_dashboard(id, &params, &state)
+		.await
+		.map_err(|status| ErrorTemplate {
+			base: base.clone(),
+			status,
+		})?;
+
+	Ok(DashboardTemplate {
+		base,
+		owner,
+		dashboard,
+	})
+}
+
 #[derive(Template, WebTemplate)]
 #[template(path = "user_reservations.html")]
 struct UserReservationsTemplate {
@@ -694,8 +840,11 @@ struct PostTemplate {
 	conflicting_textures: BTreeMap<i32, BTreeMap<u32, String>>,
 	conflict_posts: BTreeMap<i32, Post>,
 	conflict_users: BTreeMap<i64, User>,
+	song_versions: PvSearch,
 	requires_expatch: bool,
 	requires_nc: bool,
+	nc_compatibility: NcCompatibility,
+	related_posts: Vec<Post>,
 	has_required_sprites: bool,
 	has_optional_ftc_sprites: bool,
 	has_dml_pvtmb: bool,
@@ -774,6 +923,10 @@ async fn post_detail(
 
 	let body_markdown = comrak::markdown_to_html(&post.post.text, &options);
 
+	let song_versions = crate::api::songs::find_song_versions(&post.pvs.pvs, &state)
+		.await
+		.into_search();
+
 	Ok(PostTemplate {
 		user: base.user.clone(),
 		jwt: base.jwt.clone(),
@@ -812,8 +965,11 @@ async fn post_detail(
 		conflicting_textures: post.conflicting_textures,
 		conflict_posts: post.conflict_posts,
 		conflict_users: post.conflict_users,
+		song_versions,
 		requires_expatch: post.requires_expatch,
 		requires_nc: post.requires_nc,
+		nc_compatibility: post.nc_compatibility,
+		related_posts: post.related_posts,
 		has_required_sprites: post.has_required_sprites,
 		has_optional_ftc_sprites: post.has_optional_ftc_sprites,
 		has_dml_pvtmb: post.has_dml_pvtmb,
@@ -834,7 +990,11 @@ async fn search(
 	base: BaseTemplate,
 	State(state): State<AppState>,
 ) -> Result<SearchTemplate, ErrorTemplate> {
-	if query.query.is_some() || query.sort.is_some() || query.filter.is_some() {
+	if query.query.is_some()
+		|| query.sort.is_some()
+		|| query.filter.is_some()
+		|| query.author.is_some()
+	{
 		let Json(posts) =
 			crate::api::posts::search_posts(Query(query.clone()), State(state.clone()))
 				.await
@@ -943,6 +1103,65 @@ async fn pvs(base: BaseTemplate, State(state): State<AppState>) -> PvsTemplate {
 	return PvsTemplate { base, pvs };
 }
 
+#[derive(Template, WebTemplate)]
+#[template(path = "pv.html")]
+struct PvTemplate {
+	base: BaseTemplate,
+	pv: Pv,
+	nc_songs: Vec<NcSong>,
+	other_pvs: PvSearch,
+	versions: PvSearch,
+	reservations: Vec<Reservation>,
+	posts: BTreeMap<i32, Post>,
+	users: BTreeMap<i64, User>,
+}
+
+async fn pv_detail(
+	Path((post, pv_id)): Path<(i32, i32)>,
+	base: BaseTemplate,
+	State(state): State<AppState>,
+) -> Result<PvTemplate, ErrorTemplate> {
+	let user = base.user.clone().ok_or(ErrorTemplate {
+		base: base.clone(),
+		status: StatusCode::UNAUTHORIZED,
+	});
+	let Json(pv) = crate::api::ids::pv_detail(Path((post, pv_id)), user, State(state.clone()))
+		.await
+		.map_err(|(status, _)| ErrorTemplate {
+			base: base.clone(),
+			status,
+		})?;
+
+	let mut versions = crate::api::songs::find_song_versions(std::slice::from_ref(&pv.pv), &state)
+		.await
+		.into_search();
+	versions
+		.posts
+		.retain(|_, post| post.visible_to(base.user.as_ref(), &state.config));
+	// Charts sharing this pv id are already listed as other charts of this pv
+	versions.pvs.retain(|version| {
+		version.id != pv.pv.id
+			&& version
+				.post
+				.is_none_or(|post| versions.posts.contains_key(&post))
+	});
+
+	Ok(PvTemplate {
+		base,
+		pv: pv.pv,
+		nc_songs: pv.nc_songs,
+		other_pvs: PvSearch {
+			pvs: pv.other_pvs,
+			nc_songs: BTreeMap::new(),
+			posts: pv.posts.clone(),
+		},
+		versions,
+		reservations: pv.reservations,
+		posts: pv.posts,
+		users: pv.users,
+	})
+}
+
 #[derive(Template, WebTemplate)]
 #[template(path = "modules.html")]
 struct ModulesTemplate {
@@ -966,6 +1185,76 @@ async fn modules(base: BaseTemplate, State(state): State<AppState>) -> ModulesTe
 	return ModulesTemplate { base, modules };
 }
 
+#[derive(Template, WebTemplate)]
+#[template(path = "module.html")]
+struct ModuleTemplate {
+	base: BaseTemplate,
+	module: Module,
+	same_id: ModuleSearch,
+	same_costume: ModuleSearch,
+	cstm_items: CstmItemSearch,
+}
+
+async fn module_detail(
+	Path((post, module_id)): Path<(i32, i32)>,
+	base: BaseTemplate,
+	State(state): State<AppState>,
+) -> Result<ModuleTemplate, ErrorTemplate> {
+	let user = base.user.clone().ok_or(ErrorTemplate {
+		base: base.clone(),
+		status: StatusCode::UNAUTHORIZED,
+	});
+	let Json(module) = crate::api::ids::module_detail(Path((post, module_id)), user, State(state))
+		.await
+		.map_err(|(status, _)| ErrorTemplate {
+			base: base.clone(),
+			status,
+		})?;
+
+	Ok(ModuleTemplate {
+		base,
+		module: module.module,
+		same_id: module.same_id,
+		same_costume: module.same_costume,
+		cstm_items: module.cstm_items,
+	})
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "cos.html")]
+struct CosTemplate {
+	base: BaseTemplate,
+	chara: module_db::Chara,
+	cos_id: i32,
+	modules: ModuleSearch,
+	cstm_items: CstmItemSearch,
+}
+
+async fn cos_detail(
+	Path((chara, cos_id)): Path<(String, i32)>,
+	base: BaseTemplate,
+	State(state): State<AppState>,
+) -> Result<CosTemplate, ErrorTemplate> {
+	let user = base.user.clone().ok_or(ErrorTemplate {
+		base: base.clone(),
+		status: StatusCode::UNAUTHORIZED,
+	});
+	let Json(cos) = crate::api::ids::cos_detail(Path((chara, cos_id)), user, State(state))
+		.await
+		.map_err(|(status, _)| ErrorTemplate {
+			base: base.clone(),
+			status,
+		})?;
+
+	Ok(CosTemplate {
+		base,
+		chara: cos.chara,
+		cos_id: cos.cos_id,
+		modules: cos.modules,
+		cstm_items: cos.cstm_items,
+	})
+}
+
 #[derive(Template, WebTemplate)]
 #[template(path = "cstm_items.html")]
 struct CstmItemsTemplate {
@@ -1044,13 +1333,10 @@ async fn pv_spreadsheet(base: BaseTemplate, State(state): State<AppState>) -> Pv
 	let mut pvs: BTreeMap<i32, Vec<Pv>> = BTreeMap::new();
 	let mut posts: BTreeMap<i32, Post> = BTreeMap::new();
 
-	if let Ok(search) =
-		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("pvs"))
-			.with_limit(u32::MAX as usize)
-			.execute::<MeilisearchPv>()
-			.await
+	if let Ok(documents) =
+		crate::api::catalogue::all_documents::<MeilisearchPv>("pvs", None, &state).await
 	{
-		for pv in search.results {
+		for pv in documents {
 			let post = if pv.post == -1 {
 				None
 			} else if let Some(post) = posts.get(&pv.post) {
@@ -1065,11 +1351,14 @@ async fn pv_spreadsheet(base: BaseTemplate, State(state): State<AppState>) -> Pv
 			let pv = Pv {
 				uid: BASE64_STANDARD.encode(pv.uid.to_ne_bytes()),
 				id: pv.pv_id,
+				preview_url: pv.preview_url(),
 				name: pv.song_name,
 				name_en: pv.song_name_en,
 				song_info: pv.song_info,
 				song_info_en: pv.song_info_en,
 				levels: pv.levels,
+				charts: pv.charts,
+				metadata: pv.metadata,
 				post,
 			};
 
@@ -1172,13 +1461,10 @@ async fn module_spreadsheet(
 	let mut modules: BTreeMap<i32, Vec<Module>> = BTreeMap::new();
 	let mut posts: BTreeMap<i32, Post> = BTreeMap::new();
 
-	if let Ok(search) =
-		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("modules"))
-			.with_limit(u32::MAX as usize)
-			.execute::<MeilisearchModule>()
-			.await
+	if let Ok(documents) =
+		crate::api::catalogue::all_documents::<MeilisearchModule>("modules", None, &state).await
 	{
-		for module in search.results {
+		for module in documents {
 			let post = if module.post_id == -1 {
 				None
 			} else if let Some(post) = posts.get(&module.post_id) {
@@ -1347,17 +1633,15 @@ async fn cos_spreadsheet(
 	})
 	.collect::<HashMap<_, _>>();
 
-	if let Ok(search) =
-		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("modules"))
-			.with_limit(u32::MAX as usize)
-			.with_filter(&format!(
-				"chara={}",
-				serde_json::to_string(&chara).unwrap().trim_matches('\"'),
-			))
-			.execute::<MeilisearchModule>()
+	let filter = format!(
+		"chara={}",
+		serde_json::to_string(&chara).unwrap().trim_matches('\"'),
+	);
+	if let Ok(documents) =
+		crate::api::catalogue::all_documents::<MeilisearchModule>("modules", Some(&filter), &state)
 			.await
 	{
-		for module in search.results {
+		for module in documents {
 			let post = if module.post_id == -1 {
 				None
 			} else if let Some(post) = posts.get(&module.post_id) {
@@ -1476,13 +1760,11 @@ async fn cstm_item_spreadsheet(
 	let mut cstm_items: BTreeMap<i32, Vec<CstmItem>> = BTreeMap::new();
 	let mut posts: BTreeMap<i32, Post> = BTreeMap::new();
 
-	if let Ok(search) =
-		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index("cstm_items"))
-			.with_limit(u32::MAX as usize)
-			.execute::<MeilisearchCstmItem>()
+	if let Ok(documents) =
+		crate::api::catalogue::all_documents::<MeilisearchCstmItem>("cstm_items", None, &state)
 			.await
 	{
-		for cstm_item in search.results {
+		for cstm_item in documents {
 			let post = if cstm_item.post_id == -1 {
 				None
 			} else if let Some(post) = posts.get(&cstm_item.post_id) {
@@ -1801,6 +2083,8 @@ struct EditTemplate {
 	files: Vec<String>,
 	completed: Vec<i64>,
 	length: Vec<i64>,
+	lint_report: Option<crate::api::lint::LintReport>,
+	tags: Vec<Tag>,
 }
 
 async fn edit(
@@ -1887,12 +2171,20 @@ async fn edit(
 		});
 	}
 
+	let lint_report = crate::api::lint::get_lint_report(post.id, &state).await;
+	let tags = crate::api::tags::list_tags(State(state.clone()))
+		.await
+		.map(|Json(tags)| tags)
+		.unwrap_or_default();
+
 	Ok(EditTemplate {
 		base,
 		post,
 		files,
 		completed,
 		length,
+		lint_report,
+		tags,
 	})
 }
 
@@ -2005,11 +2297,8 @@ async fn db_spreadsheet(
 	base: BaseTemplate,
 	state: AppState,
 ) -> Result<DbSpreadsheetTemplate, ErrorTemplate> {
-	let Ok(entries) =
-		meilisearch_sdk::documents::DocumentsQuery::new(&state.meilisearch.index(index))
-			.with_limit(u32::MAX as usize)
-			.execute::<MeilisearchDbEntry>()
-			.await
+	let Ok(mut entries) =
+		crate::api::catalogue::all_documents::<MeilisearchDbEntry>(&index, None, &state).await
 	else {
 		return Err(ErrorTemplate {
 			base: base.clone(),
@@ -2018,7 +2307,6 @@ async fn db_spreadsheet(
 	};
 
 	let filter = entries
-		.results
 		.iter()
 		.filter(|entry| entry.post_id != -1)
 		.map(|entry| entry.post_id)
@@ -2033,6 +2321,7 @@ async fn db_spreadsheet(
 			query: None,
 			sort: None,
 			filter: Some(filter),
+			author: None,
 			limit: Some(u32::MAX as usize),
 			offset: Some(0),
 		}),
@@ -2046,7 +2335,6 @@ async fn db_spreadsheet(
 		.map(|post| (post.id, post))
 		.collect::<HashMap<_, _>>();
 
-	let mut entries = entries.results;
 	entries.sort_by(|a, b| a.id.cmp(&b.id));
 
 	Ok(DbSpreadsheetTemplate {